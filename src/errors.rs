@@ -6,14 +6,24 @@ pub enum SonioxLiveErrors {
     AudioBuildStream(#[from] cpal::BuildStreamError),
     #[error("Audio playback failure")]
     AudioPlayStream(#[from] cpal::PlayStreamError),
+    #[error("Audio pause failure")]
+    AudioPauseStream(#[from] cpal::PauseStreamError),
     #[error("Failed to get default audio config")]
     AudioConfig(#[from] cpal::DefaultStreamConfigError),
+    #[error("Unsupported audio sample format: {0}")]
+    AudioFormat(cpal::SampleFormat),
+    #[error("Failed to enumerate audio devices: {0}")]
+    AudioDevices(#[from] cpal::DevicesError),
+    #[error("Audio host is unavailable")]
+    AudioHost(#[from] cpal::HostUnavailable),
     #[error("WebSocket connection error: {0}")]
     WebSocket(#[from] tungstenite::Error),
     #[error("Server connection lost (Heartbeat failed)")]
     ConnectionLost,
     #[error("Output device is not found")]
     NotFoundOutputDevice,
+    #[error("Input device is not found")]
+    NotFoundInputDevice,
     #[error("Failed to parse JSON: {0}")]
     JsonParse(#[from] serde_json::Error),
     #[error("Failed to serialize configuration: {0}")]
@@ -35,3 +45,9 @@ impl From<&str> for SonioxLiveErrors {
         SonioxLiveErrors::Internal(s.to_string())
     }
 }
+
+impl From<String> for SonioxLiveErrors {
+    fn from(s: String) -> Self {
+        SonioxLiveErrors::Internal(s)
+    }
+}
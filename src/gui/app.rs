@@ -1,12 +1,19 @@
-use crate::gui::draw::draw_subtitles;
+use crate::gui::draw::{SubtitleStyle, draw_subtitles};
+use crate::gui::hotkeys::HotkeyListener;
+use crate::gui::layout::{LayoutEditor, LayoutOutcome};
 use crate::gui::settings::show_settings_window;
-use crate::gui::state::{AppState, StateManager};
-use crate::settings::SettingsApp;
+use crate::gui::state::{AppState, PendingState, StateManager, apply_store_settings};
+use crate::gui::status::{OverlayStatus, draw_status};
+use crate::settings::{SettingsApp, SettingsChange};
+use crate::transcription::history::TranscriptHistory;
+use crate::transcription::recorder::TranscriptRecorder;
 use crate::transcription::service::TranscriptionService;
 use crate::transcription::store::TranscriptionStore;
+use crate::transcription::transcript_log::TranscriptLog;
 use crate::types::events::SonioxEvent;
+use crate::types::hotkeys::HotkeyAction;
 use eframe::egui::{
-    Align, Area, Context, Id, Layout, Order, ViewportCommand, Visuals, WindowLevel,
+    Align, Align2, Area, Context, Id, Layout, Order, ViewportCommand, Visuals, WindowLevel,
 };
 use eframe::{App, Frame};
 use egui_notify::Toasts;
@@ -16,11 +23,28 @@ use tracing_appender::non_blocking::WorkerGuard;
 fn process_events(
     service: &mut TranscriptionService,
     store: &mut TranscriptionStore,
+    status: &mut OverlayStatus,
+    history: &mut TranscriptHistory,
+    mut recorder: Option<&mut TranscriptRecorder>,
+    transcript_log: &mut Option<TranscriptLog>,
     toasts: &mut Toasts,
 ) {
     while let Ok(event) = service.receiver.try_recv() {
         match event {
             SonioxEvent::Transcription(r) => {
+                if let Some(recorder) = recorder.as_deref_mut() {
+                    recorder.record(&r);
+                }
+                history.record(&r);
+                if let Some(log) = transcript_log
+                    && let Err(e) = log.record(&r)
+                {
+                    toasts
+                        .error(format!("Transcript log stopped: {}", e))
+                        .duration(Duration::from_secs(4))
+                        .closable(false);
+                    *transcript_log = None;
+                }
                 store.update(r);
             }
             SonioxEvent::Warning(s) => {
@@ -30,13 +54,21 @@ fn process_events(
                     .closable(false);
             }
             SonioxEvent::Error(e) => {
+                status.clear_reconnecting();
                 toasts
                     .error(e.to_string())
                     .duration(Duration::from_secs(4))
                     .closable(false);
             }
             SonioxEvent::Connected(flag_first_connection) => {
+                status.clear_reconnecting();
                 store.ensure_separator();
+                history.close();
+                if let Some(log) = transcript_log
+                    && let Err(e) = log.flush()
+                {
+                    tracing::warn!("Failed to flush transcript log: {}", e);
+                }
                 if flag_first_connection {
                     toasts
                         .info("Connected to Soniox!")
@@ -44,6 +76,12 @@ fn process_events(
                         .closable(false);
                 }
             }
+            SonioxEvent::Reconnecting { attempt, delay } => {
+                status.set_reconnecting(attempt, delay);
+            }
+            SonioxEvent::VoiceActivity(listening) => {
+                status.set_listening(listening);
+            }
         };
     }
 }
@@ -51,8 +89,16 @@ fn process_events(
 pub struct SubtitlesApp {
     settings: SettingsApp,
     store: TranscriptionStore,
+    status: OverlayStatus,
     toasts: Toasts,
+    history: TranscriptHistory,
+    recorder: TranscriptRecorder,
+    transcript_log: Option<TranscriptLog>,
     manager: StateManager,
+    /// Only exists while a session runs, so the system-wide keys are free otherwise.
+    hotkeys: Option<HotkeyListener>,
+    overlay_hidden: bool,
+    layout_editor: LayoutEditor,
     frame_counter: u64,
     _guard: WorkerGuard,
 }
@@ -61,8 +107,15 @@ impl SubtitlesApp {
     pub fn new(settings: SettingsApp, guard: WorkerGuard) -> Self {
         Self {
             store: TranscriptionStore::new(settings.max_blocks()),
+            status: OverlayStatus::default(),
             toasts: Toasts::new(),
+            history: open_history(&settings),
+            recorder: TranscriptRecorder::new(),
+            transcript_log: None,
             manager: StateManager::new(),
+            hotkeys: None,
+            overlay_hidden: false,
+            layout_editor: LayoutEditor::default(),
             settings,
             frame_counter: 0,
             _guard: guard,
@@ -70,43 +123,245 @@ impl SubtitlesApp {
     }
 }
 
+fn open_history(settings: &SettingsApp) -> TranscriptHistory {
+    let Some(path) = settings.history_file() else {
+        return TranscriptHistory::new();
+    };
+    TranscriptHistory::load(path).unwrap_or_else(|e| {
+        tracing::error!("Failed to load history from {}: {}", path, e);
+        TranscriptHistory::new()
+    })
+}
+
+pub(crate) fn export_transcript(
+    recorder: &TranscriptRecorder,
+    settings: &SettingsApp,
+    toasts: &mut Toasts,
+) {
+    match recorder.export(
+        settings.export_dir(),
+        settings.export_srt(),
+        settings.export_vtt(),
+    ) {
+        Ok(paths) if paths.is_empty() => {
+            toasts
+                .warning("Nothing to export yet")
+                .duration(Duration::from_secs(4))
+                .closable(false);
+        }
+        Ok(paths) => {
+            tracing::info!("transcript exported to {:?}", paths);
+            toasts
+                .success(format!("Transcript saved to {}", settings.export_dir()))
+                .duration(Duration::from_secs(4))
+                .closable(false);
+        }
+        Err(e) => {
+            toasts
+                .error(format!("Failed to export transcript: {}", e))
+                .duration(Duration::from_secs(5))
+                .closable(false);
+        }
+    }
+}
+
+impl SubtitlesApp {
+    fn open_transcript_log(&mut self) {
+        if !self.settings.enable_transcript_log() {
+            return;
+        }
+        match TranscriptLog::create(
+            self.settings.transcript_log_path(),
+            self.settings.transcript_log_format(),
+            self.history.session(),
+        ) {
+            Ok((log, path)) => {
+                tracing::info!("Writing transcript log to {:?}", path);
+                self.transcript_log = Some(log);
+            }
+            Err(e) => {
+                self.toasts
+                    .error(format!("Failed to open transcript log: {}", e))
+                    .duration(Duration::from_secs(5))
+                    .closable(false);
+            }
+        }
+    }
+
+    fn handle_hotkeys(&mut self, ctx: &Context) {
+        let Some(listener) = self.hotkeys.as_mut() else {
+            return;
+        };
+        let actions = listener.poll(ctx, self.settings.hotkeys());
+        if actions.is_empty() {
+            return;
+        }
+        tracing::debug!("hotkeys pressed: {:?}", actions);
+
+        let in_settings = match self.manager.app_state() {
+            AppState::Layout(_) => return,
+            state => matches!(state, AppState::Settings(_)),
+        };
+        // A key pressed in another application must never start a paid session.
+        let Some(service) = self.manager.service_mut() else {
+            return;
+        };
+        let mut switch_to = None;
+        for action in actions {
+            match action {
+                HotkeyAction::ToggleOverlay if in_settings => {
+                    switch_to = Some(PendingState::Overlay)
+                }
+                HotkeyAction::ToggleOverlay => self.overlay_hidden = !self.overlay_hidden,
+                HotkeyAction::PauseCapture => {
+                    if let Err(e) = service.toggle_pause() {
+                        self.toasts
+                            .error(e.to_string())
+                            .duration(Duration::from_secs(4))
+                            .closable(false);
+                    }
+                }
+                HotkeyAction::OpenSettings => switch_to = Some(PendingState::Settings),
+                HotkeyAction::ClearSubtitles => self.store.clear(),
+                HotkeyAction::Finalize => service.finalize(),
+            }
+        }
+        if let Some(state) = switch_to {
+            self.manager.switch(state);
+            ctx.request_repaint();
+        }
+    }
+}
+
 impl App for SubtitlesApp {
     fn update(&mut self, ctx: &Context, _: &mut Frame) {
-        if let Err(err) = self.manager.resolve(ctx, &mut self.store, &self.settings) {
-            self.toasts.error(format!("{:?}", err)).closable(false);
+        let had_session = self.manager.has_session();
+        let change = self
+            .manager
+            .resolve(ctx, &mut self.store, &self.settings)
+            .unwrap_or_else(|err| {
+                self.toasts.error(format!("{:?}", err)).closable(false);
+                SettingsChange::None
+            });
+        match (had_session, self.manager.has_session()) {
+            (true, false) => {
+                self.hotkeys = None;
+                self.history.close();
+                self.transcript_log = None;
+                if self.settings.enable_recording() {
+                    export_transcript(&self.recorder, &self.settings, &mut self.toasts);
+                }
+            }
+            (false, true) => {
+                self.history.begin_session();
+                self.recorder.reset();
+                self.open_transcript_log();
+                self.overlay_hidden = false;
+                self.status = OverlayStatus::default();
+                self.hotkeys = Some(HotkeyListener::new(ctx, self.settings.hotkeys()));
+            }
+            // The new capture reports its own voice activity, if the gate is still on.
+            (true, true) if change == SettingsChange::Restart => {
+                self.status = OverlayStatus::default();
+            }
+            _ => {}
         }
-        let manager = &mut self.manager;
+        self.handle_hotkeys(ctx);
 
-        match manager.app_state_mut() {
-            AppState::Config => {
-                show_settings_window(ctx, &mut self.settings, &mut self.manager, &mut self.toasts)
-            }
-            AppState::Overlay(service) => {
-                let timeout = Duration::from_secs(15);
-                let ctx_for_plan = ctx.clone();
-                self.store.clear_if_silent(timeout);
-                self.store.schedule(ctx_for_plan, timeout);
+        if let Some(service) = self.manager.service_mut() {
+            let timeout = Duration::from_secs(15);
+            let ctx_for_plan = ctx.clone();
+            self.store.clear_if_silent(timeout);
+            self.store.schedule(ctx_for_plan, timeout);
+
+            let recorder = self
+                .settings
+                .enable_recording()
+                .then_some(&mut self.recorder);
+            process_events(
+                service,
+                &mut self.store,
+                &mut self.status,
+                &mut self.history,
+                recorder,
+                &mut self.transcript_log,
+                &mut self.toasts,
+            );
+            self.status.set_paused(service.is_paused());
+            self.status.schedule(ctx);
+        }
 
-                process_events(service, &mut self.store, &mut self.toasts);
+        match self.manager.app_state() {
+            AppState::Config | AppState::Settings(_) => {
+                if self.manager.has_session() {
+                    apply_store_settings(&mut self.store, &self.settings);
+                }
+                show_settings_window(
+                    ctx,
+                    &mut self.settings,
+                    &mut self.manager,
+                    &mut self.history,
+                    &self.recorder,
+                    &mut self.toasts,
+                )
+            }
+            AppState::Layout(_) => {
+                let outcome = self.layout_editor.show(ctx, &self.settings);
+                if !matches!(outcome, LayoutOutcome::Editing) {
+                    if let LayoutOutcome::Done = outcome {
+                        self.layout_editor.apply(ctx, &mut self.settings);
+                        self.toasts
+                            .info("Layout updated. Save the settings to keep it.")
+                            .duration(Duration::from_secs(4))
+                            .closable(false);
+                    }
+                    self.layout_editor.reset();
+                    let back = if self.manager.has_session() {
+                        PendingState::Settings
+                    } else {
+                        PendingState::Config
+                    };
+                    self.manager.switch(back);
+                    ctx.request_repaint();
+                }
+            }
+            AppState::Overlay(_) => {
                 if self.settings.enable_high_priority() && self.frame_counter >= 100 {
                     ctx.send_viewport_cmd(ViewportCommand::WindowLevel(WindowLevel::AlwaysOnTop));
                     self.frame_counter = 0;
                 }
-                let (anchor, offset) = self.settings.get_anchor();
-                Area::new(Id::from("subtitles_area"))
-                    .anchor(anchor, offset)
-                    .order(Order::Foreground)
-                    .show(ctx, |ui| {
-                        ui.with_layout(Layout::top_down(Align::LEFT), |ui| {
+                if !self.overlay_hidden {
+                    let (anchor, offset) = self.settings.get_anchor();
+                    let subtitles = Area::new(Id::from("subtitles_area"))
+                        .anchor(anchor, offset)
+                        .order(Order::Foreground)
+                        .show(ctx, |ui| {
                             draw_subtitles(
                                 ui,
                                 &self.store,
-                                self.settings.font_size(),
-                                self.settings.text_color(),
-                                self.settings.get_background_color(),
+                                &SubtitleStyle::from_settings(
+                                    &self.settings,
+                                    self.history.speakers(),
+                                ),
                             );
                         });
-                    });
+                    // Status rows sit on top of the box so the anchor only ever places
+                    // the subtitles, the same box the layout editor shows.
+                    Area::new(Id::from("status_area"))
+                        .fixed_pos(subtitles.response.rect.left_top())
+                        .pivot(Align2::LEFT_BOTTOM)
+                        .order(Order::Foreground)
+                        .show(ctx, |ui| {
+                            ui.with_layout(Layout::top_down(Align::LEFT), |ui| {
+                                draw_status(
+                                    ui,
+                                    &self.status,
+                                    self.settings.font_size(),
+                                    self.settings.text_color(),
+                                );
+                            });
+                        });
+                }
 
                 self.frame_counter += 1;
             }
@@ -115,6 +370,21 @@ impl App for SubtitlesApp {
         self.toasts.show(ctx);
     }
 
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.history.close();
+        self.transcript_log = None;
+        if !self.manager.has_session() || !self.settings.enable_recording() {
+            return;
+        }
+        if let Err(e) = self.recorder.export(
+            self.settings.export_dir(),
+            self.settings.export_srt(),
+            self.settings.export_vtt(),
+        ) {
+            tracing::error!("failed to export transcript: {}", e);
+        }
+    }
+
     fn clear_color(&self, _visuals: &Visuals) -> [f32; 4] {
         [0.0, 0.0, 0.0, 0.0]
     }
@@ -1,3 +1,4 @@
+use crate::types::languages::LanguageHint;
 use eframe::egui::Color32;
 use eframe::epaint::Hsva;
 
@@ -14,3 +15,13 @@ pub(crate) fn get_interim_color(main_color: Color32) -> Color32 {
     hsva.a = 1.0;
     Color32::from(hsva)
 }
+
+/// Spreads languages around the hue circle so neighbours in the list look different.
+pub(crate) fn get_language_color(language: LanguageHint) -> Color32 {
+    let index = LanguageHint::all()
+        .iter()
+        .position(|l| *l == language)
+        .unwrap_or_default();
+    let hue = (index as f32 * 0.618_034).fract();
+    Color32::from(Hsva::new(hue, 0.55, 1.0, 1.0))
+}
@@ -1,19 +1,71 @@
-use crate::gui::color::get_interim_color;
-use crate::transcription::replicas::{VisualReplica, prepare_replicas};
+use crate::gui::color::{get_interim_color, get_language_color};
+use crate::settings::SettingsApp;
+use crate::transcription::replicas::{TextElement, VisualReplica, prepare_replicas};
 use crate::transcription::store::TranscriptionStore;
+use crate::types::speakers::SpeakerRegistry;
+use crate::types::subtitles::{ConfidenceMode, ConfidenceStyle, LanguageDisplay};
+use crate::types::translation::TranslationMode;
 use eframe::egui::text::LayoutJob;
-use eframe::egui::{Color32, FontId, Frame, LayerId, Order, Rect, Stroke, TextFormat, Ui, Vec2};
+use eframe::egui::{
+    Color32, FontId, Frame, Galley, LayerId, Order, Painter, Pos2, Rect, Sense, Stroke, TextFormat,
+    Ui, Vec2,
+};
 use eframe::epaint::StrokeKind;
+use std::sync::Arc;
 
 const ANIM_TIME: f32 = 0.08;
+const ORIGINAL_SCALE: f32 = 0.75;
+const OUTLINE_STEPS: usize = 12;
 
-pub fn draw_subtitles(
-    ui: &mut Ui,
-    store: &TranscriptionStore,
-    font_size: f32,
-    text_color: Color32,
-    background_color: Color32,
-) {
+/// Everything `draw_subtitles` takes from the settings, resolved once per frame.
+pub struct SubtitleStyle<'a> {
+    pub font_size: f32,
+    pub max_width: Option<f32>,
+    pub text_color: Color32,
+    pub background_color: Color32,
+    pub corner_radius: f32,
+    pub line_spacing: f32,
+    pub outline: Option<Stroke>,
+    pub shadow: Option<(Vec2, Color32)>,
+    pub show_direction: bool,
+    pub confidence: ConfidenceStyle,
+    pub languages: LanguageDisplay,
+    pub speakers: &'a SpeakerRegistry,
+}
+
+impl<'a> SubtitleStyle<'a> {
+    pub fn from_settings(settings: &'a SettingsApp, speakers: &'a SpeakerRegistry) -> Self {
+        Self {
+            font_size: settings.font_size(),
+            max_width: settings.subtitle_width(),
+            text_color: settings.text_color(),
+            background_color: settings.get_background_color(),
+            corner_radius: settings.corner_radius(),
+            line_spacing: settings.line_spacing(),
+            outline: settings.outline(),
+            shadow: settings.shadow(),
+            show_direction: settings.enable_translate()
+                && settings.translation_mode() == TranslationMode::TwoWay,
+            confidence: settings.confidence_style(),
+            languages: settings.language_display(),
+            speakers,
+        }
+    }
+}
+
+/// Padding between the edge of the subtitle box and its text.
+pub const SUBTITLE_MARGIN: f32 = 16.0;
+
+/// Widest the subtitle box may get, margins included. Without an explicit width it
+/// follows the screen.
+pub fn max_subtitle_width(width: Option<f32>, screen_width: f32) -> f32 {
+    match width {
+        Some(width) => width.min(screen_width),
+        None => (screen_width * 0.8).min(1200.0),
+    }
+}
+
+pub fn draw_subtitles(ui: &mut Ui, store: &TranscriptionStore, style: &SubtitleStyle) {
     let replicas = prepare_replicas(store);
     if replicas.is_empty() {
         return;
@@ -25,8 +77,7 @@ pub fn draw_subtitles(
     let visible_replicas = replicas.iter().skip(start_index);
 
     let screen_width = ui.ctx().content_rect().width();
-    let max_width = (screen_width * 0.8).min(1200.0);
-    let interim_color = get_interim_color(text_color);
+    let max_width = max_subtitle_width(style.max_width, screen_width);
 
     let id = ui.id().with("subtitles_anim_box");
     let last_target_size = ui.data(|d| d.get_temp::<Vec2>(id)).unwrap_or(Vec2::ZERO);
@@ -40,14 +91,14 @@ pub fn draw_subtitles(
 
     let inner = Frame::new()
         .fill(Color32::TRANSPARENT)
-        .corner_radius(12.0)
-        .inner_margin(16.0)
+        .corner_radius(style.corner_radius)
+        .inner_margin(SUBTITLE_MARGIN)
         .show(ui, |ui| {
-            ui.set_max_width(max_width);
+            ui.set_max_width(max_width - SUBTITLE_MARGIN * 2.0);
             ui.vertical(|ui| {
                 for replica in visible_replicas {
-                    draw_replica_row(ui, replica, font_size, text_color, interim_color);
-                    ui.add_space(4.0);
+                    draw_replica_row(ui, replica, style);
+                    ui.add_space(style.line_spacing);
                 }
             });
         });
@@ -55,15 +106,15 @@ pub fn draw_subtitles(
     let target_rect = inner.response.rect;
     let target_size = target_rect.size();
 
-    if background_color != Color32::TRANSPARENT {
+    if style.background_color != Color32::TRANSPARENT {
         let animated_rect = Rect::from_center_size(target_rect.center(), current_animated_size);
         let painter = ui.painter().clone();
         painter
             .with_layer_id(LayerId::new(Order::Background, id))
             .rect(
                 animated_rect,
-                12.0,
-                background_color,
+                style.corner_radius,
+                style.background_color,
                 Stroke::NONE,
                 StrokeKind::Middle,
             );
@@ -75,45 +126,151 @@ pub fn draw_subtitles(
     }
 }
 
-fn draw_replica_row(
-    ui: &mut Ui,
-    replica: &VisualReplica,
-    font_size: f32,
-    text_color: Color32,
-    interim_color: Color32,
-) {
-    let mut job = LayoutJob::default();
-    let mut last_ends_with_space = false;
-    job.wrap.break_anywhere = false;
-    if let Some(id) = &replica.speaker {
-        let speaker_format = TextFormat {
-            font_id: FontId::proportional(font_size),
-            color: text_color,
-            ..Default::default()
-        };
-        job.append(id, 0.0, speaker_format.clone());
-        job.append(": ", 0.0, speaker_format);
-        last_ends_with_space = true;
+fn draw_replica_row(ui: &mut Ui, replica: &VisualReplica, style: &SubtitleStyle) {
+    let font_size = style.font_size;
+    let text_color = match (style.languages, replica.language) {
+        (LanguageDisplay::Color, Some(language)) => get_language_color(language),
+        _ => style.text_color,
+    };
+    let interim_color = get_interim_color(text_color);
+
+    let speaker = replica.speaker.map(|id| {
+        let profile = style.speakers.get(id);
+        let color = profile.map(|p| Color32::from_rgb(p.color.0, p.color.1, p.color.2));
+        (style.speakers.label(id), color)
+    });
+    let mut prefix = match (replica.direction, replica.language) {
+        (Some((source, target)), _) if style.show_direction => Some(format!(
+            "{} → {}  ",
+            source.code().to_uppercase(),
+            target.code().to_uppercase()
+        )),
+        (_, Some(language)) if style.languages == LanguageDisplay::Tag => {
+            Some(format!("[{}] ", language.code().to_uppercase()))
+        }
+        _ => None,
+    };
+    if !replica.originals.is_empty() {
+        let original_size = font_size * ORIGINAL_SCALE;
+        let original_color = text_color.gamma_multiply(0.7);
+        let original_interim = interim_color.gamma_multiply(0.7);
+        let job = replica_job(
+            prefix.take(),
+            speaker,
+            &replica.originals,
+            None,
+            |is_interim| TextFormat {
+                font_id: FontId::proportional(original_size),
+                color: if is_interim {
+                    original_interim
+                } else {
+                    original_color
+                },
+                italics: true,
+                ..Default::default()
+            },
+        );
+        paint_text(ui, job, style);
+    }
+    if replica.elements.is_empty() {
+        return;
     }
 
-    for elem in replica.elements.iter() {
-        let format = TextFormat {
+    let speaker = speaker.filter(|_| replica.originals.is_empty());
+    let job = replica_job(
+        prefix,
+        speaker,
+        &replica.elements,
+        Some(style.confidence),
+        |is_interim| TextFormat {
             font_id: FontId::proportional(font_size),
-            color: if elem.is_interim {
+            color: if is_interim {
                 interim_color
             } else {
                 text_color
             },
             ..Default::default()
-        };
-        let mut text = elem.text;
-        if last_ends_with_space && text.starts_with(' ') {
-            text = text.trim_start();
+        },
+    );
+    paint_text(ui, job, style);
+}
+
+/// Lays the text out like a wrapping label would.
+fn paint_text(ui: &mut Ui, mut job: LayoutJob, style: &SubtitleStyle) {
+    job.wrap.max_width = ui.available_width();
+    let galley = ui.painter().layout_job(job);
+    let (rect, _) = ui.allocate_exact_size(galley.size(), Sense::hover());
+    if !ui.is_rect_visible(rect) {
+        return;
+    }
+
+    paint_galley(ui.painter(), rect.min, galley, style);
+}
+
+/// Paints laid out subtitle text at `pos` with the shadow and outline from `style`.
+pub fn paint_galley(painter: &Painter, pos: Pos2, galley: Arc<Galley>, style: &SubtitleStyle) {
+    if let Some((offset, color)) = style.shadow {
+        painter.galley_with_override_text_color(pos + offset, galley.clone(), color);
+    }
+    if let Some(outline) = style.outline {
+        for step in 0..OUTLINE_STEPS {
+            let angle = std::f32::consts::TAU * step as f32 / OUTLINE_STEPS as f32;
+            let offset = Vec2::angled(angle) * outline.width;
+            painter.galley_with_override_text_color(pos + offset, galley.clone(), outline.color);
         }
+    }
+    painter.galley(pos, galley, style.text_color);
+}
 
-        job.append(text, 0.0, format);
-        last_ends_with_space = text.ends_with(' ');
+fn replica_job(
+    prefix: Option<String>,
+    speaker: Option<(&str, Option<Color32>)>,
+    elements: &[TextElement],
+    confidence: Option<ConfidenceStyle>,
+    format: impl Fn(bool) -> TextFormat,
+) -> LayoutJob {
+    let mut job = LayoutJob::default();
+    let mut last_ends_with_space = false;
+    job.wrap.break_anywhere = false;
+    if let Some(prefix) = prefix {
+        job.append(&prefix, 0.0, format(true));
+    }
+    if let Some((label, color)) = speaker {
+        let mut speaker_format = format(false);
+        if let Some(color) = color {
+            speaker_format.color = color;
+        }
+        job.append(label, 0.0, speaker_format.clone());
+        job.append(": ", 0.0, speaker_format);
+        last_ends_with_space = true;
+    }
+
+    for elem in elements.iter() {
+        for (segment, score) in elem.segments() {
+            let mut format = format(elem.is_interim);
+            if let Some(style) = confidence {
+                if style.is_hidden(score) {
+                    continue;
+                }
+                if style.is_marked(score) {
+                    match style.mode {
+                        ConfidenceMode::Dim => format.color = format.color.gamma_multiply(0.45),
+                        ConfidenceMode::Underline => {
+                            format.underline = Stroke::new(1.0, format.color)
+                        }
+                        ConfidenceMode::Off => {}
+                    }
+                }
+            }
+
+            let mut text = segment;
+            if last_ends_with_space && text.starts_with(' ') {
+                text = text.trim_start();
+            }
+            job.append(text, 0.0, format);
+            last_ends_with_space = text.ends_with(' ');
+        }
     }
 
-    ui.label(job);
+    job
 }
@@ -0,0 +1,285 @@
+use crate::types::hotkeys::{HotkeyAction, HotkeyBindings, KeyBinding};
+#[cfg(not(windows))]
+use eframe::egui::Event;
+use eframe::egui::{Context, Id};
+
+/// Set by the key-binding editor while it waits for a key press.
+pub fn capture_id() -> Id {
+    Id::new("hotkey_capture")
+}
+
+pub fn capturing(ctx: &Context) -> Option<HotkeyAction> {
+    ctx.data(|d| d.get_temp::<HotkeyAction>(capture_id()))
+}
+
+/// Turns key presses into [`HotkeyAction`]s.
+///
+/// On Windows the bindings are registered as system hotkeys, so they work while the
+/// passthrough overlay never gets focus and other applications don't receive them.
+/// Elsewhere only the key events egui receives are used, which needs the window to
+/// be focused.
+pub struct HotkeyListener {
+    bindings: HotkeyBindings,
+    capturing: bool,
+    #[cfg(windows)]
+    global: global::GlobalHotkeys,
+}
+
+impl HotkeyListener {
+    #[cfg_attr(not(windows), allow(unused_variables))]
+    pub fn new(ctx: &Context, bindings: &HotkeyBindings) -> Self {
+        Self {
+            bindings: bindings.clone(),
+            capturing: false,
+            #[cfg(windows)]
+            global: global::GlobalHotkeys::spawn(ctx.clone(), bindings.clone()),
+        }
+    }
+
+    /// Actions triggered since the last frame. Nothing fires while a binding is being edited.
+    pub fn poll(&mut self, ctx: &Context, bindings: &HotkeyBindings) -> Vec<HotkeyAction> {
+        let capturing = capturing(ctx).is_some();
+        if *bindings != self.bindings || capturing != self.capturing {
+            self.bindings = bindings.clone();
+            self.capturing = capturing;
+            // Released while the editor waits, so the combination reaches it.
+            #[cfg(windows)]
+            self.global
+                .set_bindings((!capturing).then(|| bindings.clone()));
+        }
+
+        #[cfg(windows)]
+        {
+            let actions = self.global.drain();
+            if capturing { Vec::new() } else { actions }
+        }
+        #[cfg(not(windows))]
+        {
+            if capturing {
+                return Vec::new();
+            }
+            self.bindings
+                .iter()
+                .filter(|&(_, binding)| consume_binding(ctx, binding))
+                .map(|(action, _)| action)
+                .collect()
+        }
+    }
+}
+
+/// Unlike `consume_key`, extra modifiers don't match, so `Ctrl+C` and `Ctrl+Shift+C` stay apart.
+#[cfg(not(windows))]
+fn consume_binding(ctx: &Context, binding: KeyBinding) -> bool {
+    ctx.input_mut(|i| {
+        let before = i.events.len();
+        i.events.retain(|event| {
+            !matches!(
+                event,
+                Event::Key { key, pressed: true, repeat: false, modifiers, .. }
+                    if KeyBinding::from_input(*key, *modifiers) == binding
+            )
+        });
+        i.events.len() != before
+    })
+}
+
+#[cfg(windows)]
+mod global {
+    use super::{HotkeyAction, HotkeyBindings, KeyBinding};
+    use eframe::egui::{Context, Key};
+    use std::sync::mpsc::{Receiver, Sender, channel};
+    use std::sync::{Arc, Mutex};
+    use windows_sys::Win32::System::Threading::GetCurrentThreadId;
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+        MOD_ALT, MOD_CONTROL, MOD_NOREPEAT, MOD_SHIFT, RegisterHotKey, UnregisterHotKey,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        GetMessageW, MSG, PM_NOREMOVE, PeekMessageW, PostThreadMessageW, WM_APP, WM_HOTKEY, WM_QUIT,
+    };
+
+    /// Tells the hotkey thread to register the shared bindings again.
+    const WM_REBIND: u32 = WM_APP + 1;
+
+    /// Bindings the hotkey thread registers, `None` while the editor waits for a key.
+    type Shared = Arc<Mutex<Option<HotkeyBindings>>>;
+
+    /// Hotkeys registered with `RegisterHotKey`, so Windows delivers them to this app
+    /// only and other applications never see the combination.
+    pub struct GlobalHotkeys {
+        bindings: Shared,
+        receiver: Receiver<HotkeyAction>,
+        thread_id: Option<u32>,
+    }
+
+    impl GlobalHotkeys {
+        pub fn spawn(ctx: Context, bindings: HotkeyBindings) -> Self {
+            let bindings = Arc::new(Mutex::new(Some(bindings)));
+            let (sender, receiver) = channel();
+            let (ready, thread_id) = channel();
+            let shared = bindings.clone();
+            let spawned = std::thread::Builder::new()
+                .name("hotkeys".into())
+                .spawn(move || listen(ctx, shared, sender, ready));
+            let thread_id = match spawned {
+                Ok(_) => thread_id.recv().ok(),
+                Err(e) => {
+                    tracing::error!("Failed to spawn hotkey thread: {}", e);
+                    None
+                }
+            };
+            Self {
+                bindings,
+                receiver,
+                thread_id,
+            }
+        }
+
+        pub fn set_bindings(&self, bindings: Option<HotkeyBindings>) {
+            if let Ok(mut shared) = self.bindings.lock() {
+                *shared = bindings;
+            }
+            self.post(WM_REBIND);
+        }
+
+        pub fn drain(&self) -> Vec<HotkeyAction> {
+            self.receiver.try_iter().collect()
+        }
+
+        fn post(&self, message: u32) {
+            if let Some(thread_id) = self.thread_id {
+                // SAFETY: posting to a thread that already has a message queue.
+                unsafe { PostThreadMessageW(thread_id, message, 0, 0) };
+            }
+        }
+    }
+
+    impl Drop for GlobalHotkeys {
+        fn drop(&mut self) {
+            self.post(WM_QUIT);
+        }
+    }
+
+    /// Runs the message loop of the hotkey thread. The hotkeys belong to this thread,
+    /// so they are registered, fired and released here.
+    fn listen(ctx: Context, bindings: Shared, sender: Sender<HotkeyAction>, ready: Sender<u32>) {
+        let mut msg = MSG::default();
+        // SAFETY: peeking creates the message queue, so posts can't get lost before the loop.
+        unsafe {
+            PeekMessageW(&mut msg, std::ptr::null_mut(), 0, 0, PM_NOREMOVE);
+            let _ = ready.send(GetCurrentThreadId());
+        }
+
+        let mut registered = register(&bindings);
+        // SAFETY: `msg` outlives every call; a null window takes the thread's messages.
+        while unsafe { GetMessageW(&mut msg, std::ptr::null_mut(), 0, 0) } > 0 {
+            match msg.message {
+                WM_HOTKEY => {
+                    let Some(&action) = registered.get(msg.wParam) else {
+                        continue;
+                    };
+                    if sender.send(action).is_err() {
+                        break;
+                    }
+                    ctx.request_repaint();
+                }
+                WM_REBIND => {
+                    unregister(&registered);
+                    registered = register(&bindings);
+                }
+                _ => {}
+            }
+        }
+        unregister(&registered);
+    }
+
+    /// Registers every binding with its index as id. A combination another application
+    /// already owns is skipped.
+    fn register(bindings: &Shared) -> Vec<HotkeyAction> {
+        let Ok(Some(current)) = bindings.lock().map(|b| b.clone()) else {
+            return Vec::new();
+        };
+        let mut registered = Vec::new();
+        for (action, binding) in current.iter() {
+            let Some(vkey) = virtual_key(binding.key) else {
+                continue;
+            };
+            // SAFETY: a null window binds the hotkey to the calling thread.
+            let ok = unsafe {
+                RegisterHotKey(
+                    std::ptr::null_mut(),
+                    registered.len() as i32,
+                    modifiers(binding),
+                    vkey as u32,
+                )
+            };
+            if ok == 0 {
+                tracing::warn!("Hotkey {} is already taken by another application", binding);
+                continue;
+            }
+            registered.push(action);
+        }
+        registered
+    }
+
+    fn unregister(registered: &[HotkeyAction]) {
+        for id in 0..registered.len() {
+            // SAFETY: releases a hotkey this thread registered.
+            unsafe { UnregisterHotKey(std::ptr::null_mut(), id as i32) };
+        }
+    }
+
+    fn modifiers(binding: KeyBinding) -> u32 {
+        let mut modifiers = MOD_NOREPEAT;
+        if binding.ctrl {
+            modifiers |= MOD_CONTROL;
+        }
+        if binding.shift {
+            modifiers |= MOD_SHIFT;
+        }
+        if binding.alt {
+            modifiers |= MOD_ALT;
+        }
+        modifiers
+    }
+
+    fn virtual_key(key: Key) -> Option<i32> {
+        let name = key.name();
+        if name.len() == 1 && name.as_bytes()[0].is_ascii_alphanumeric() {
+            return Some(name.as_bytes()[0].to_ascii_uppercase() as i32);
+        }
+        if let Some(n) = name.strip_prefix('F').and_then(|n| n.parse::<i32>().ok())
+            && (1..=24).contains(&n)
+        {
+            return Some(0x70 + n - 1);
+        }
+        Some(match key {
+            Key::Backspace => 0x08,
+            Key::Tab => 0x09,
+            Key::Enter => 0x0D,
+            Key::Escape => 0x1B,
+            Key::Space => 0x20,
+            Key::PageUp => 0x21,
+            Key::PageDown => 0x22,
+            Key::End => 0x23,
+            Key::Home => 0x24,
+            Key::ArrowLeft => 0x25,
+            Key::ArrowUp => 0x26,
+            Key::ArrowRight => 0x27,
+            Key::ArrowDown => 0x28,
+            Key::Insert => 0x2D,
+            Key::Delete => 0x2E,
+            Key::Semicolon => 0xBA,
+            Key::Equals | Key::Plus => 0xBB,
+            Key::Comma => 0xBC,
+            Key::Minus => 0xBD,
+            Key::Period => 0xBE,
+            Key::Slash => 0xBF,
+            Key::Backtick => 0xC0,
+            Key::OpenBracket => 0xDB,
+            Key::Backslash => 0xDC,
+            Key::CloseBracket => 0xDD,
+            Key::Quote => 0xDE,
+            _ => return None,
+        })
+    }
+}
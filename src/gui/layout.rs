@@ -0,0 +1,174 @@
+use crate::gui::draw::{SUBTITLE_MARGIN, SubtitleStyle, max_subtitle_width, paint_galley};
+use crate::settings::{MIN_SUBTITLE_WIDTH, SettingsApp};
+use crate::types::overlay::OverlayAnchor;
+use crate::types::speakers::SpeakerRegistry;
+use eframe::egui::{
+    self, Align, Align2, Area, Button, Color32, Context, CursorIcon, FontId, Frame, Id, Key,
+    Modifiers, Order, Pos2, Rect, Sense, Stroke, Vec2, vec2,
+};
+use eframe::epaint::StrokeKind;
+
+const PREVIEW_TEXT: &str = "Speaker 1: This is how your subtitles will look on the screen. \
+    Drag the box to move it and pull its right edge to change the width.";
+const HANDLE_WIDTH: f32 = 14.0;
+
+pub enum LayoutOutcome {
+    Editing,
+    Done,
+    Cancelled,
+}
+
+/// Full-screen "edit layout" mode: a preview box that can be dragged and resized
+/// where the subtitles will actually appear.
+#[derive(Default)]
+pub struct LayoutEditor {
+    rect: Option<Rect>,
+}
+
+impl LayoutEditor {
+    pub fn reset(&mut self) {
+        self.rect = None;
+    }
+
+    pub fn show(&mut self, ctx: &Context, settings: &SettingsApp) -> LayoutOutcome {
+        let mut outcome = LayoutOutcome::Editing;
+        if ctx.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Escape)) {
+            outcome = LayoutOutcome::Cancelled;
+        }
+        if ctx.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Enter)) {
+            outcome = LayoutOutcome::Done;
+        }
+
+        egui::CentralPanel::default()
+            .frame(Frame::NONE.fill(Color32::from_black_alpha(90)))
+            .show(ctx, |ui| {
+                let screen = ui.max_rect();
+                let id = Id::new("layout_editor");
+                let width = self.rect.map_or_else(
+                    || max_subtitle_width(settings.subtitle_width(), screen.width()),
+                    |rect| rect.width(),
+                );
+                let galley = ui.painter().layout(
+                    PREVIEW_TEXT.to_owned(),
+                    FontId::proportional(settings.font_size()),
+                    settings.text_color(),
+                    width - SUBTITLE_MARGIN * 2.0,
+                );
+                let size = vec2(width, galley.size().y + SUBTITLE_MARGIN * 2.0);
+                let rect = self.rect.get_or_insert_with(|| {
+                    let (align, offset) = settings.get_anchor();
+                    align.anchor_size(align.pos_in_rect(&screen) + offset, size)
+                });
+                rect.max.y = rect.min.y + size.y;
+
+                let handle = Rect::from_min_max(
+                    Pos2::new(rect.max.x - HANDLE_WIDTH / 2.0, rect.min.y),
+                    Pos2::new(rect.max.x + HANDLE_WIDTH / 2.0, rect.max.y),
+                );
+                let resize = ui
+                    .interact(handle, id.with("resize"), Sense::drag())
+                    .on_hover_cursor(CursorIcon::ResizeHorizontal);
+                let body = ui.interact(*rect, id.with("body"), Sense::drag());
+                if resize.dragged() {
+                    rect.max.x = (rect.max.x + resize.drag_delta().x)
+                        .max(rect.min.x + MIN_SUBTITLE_WIDTH)
+                        .min(screen.max.x);
+                } else if body.dragged() {
+                    *rect = rect.translate(body.drag_delta());
+                    ctx.set_cursor_icon(CursorIcon::Grabbing);
+                } else if body.hovered() {
+                    ctx.set_cursor_icon(CursorIcon::Grab);
+                }
+                *rect = keep_inside(*rect, screen);
+
+                let speakers = SpeakerRegistry::default();
+                let style = SubtitleStyle::from_settings(settings, &speakers);
+                let painter = ui.painter();
+                painter.rect_filled(*rect, style.corner_radius, style.background_color);
+                painter.rect_stroke(
+                    *rect,
+                    style.corner_radius,
+                    Stroke::new(1.5, ui.visuals().selection.stroke.color),
+                    StrokeKind::Outside,
+                );
+                paint_galley(
+                    painter,
+                    rect.min + vec2(SUBTITLE_MARGIN, SUBTITLE_MARGIN),
+                    galley,
+                    &style,
+                );
+                painter.rect_filled(handle.shrink2(vec2(4.0, 8.0)), 3.0, Color32::GRAY);
+
+                let (align, offset) = placement(screen, *rect);
+                painter.circle_filled(align.pos_in_rect(&screen), 6.0, Color32::ORANGE);
+                painter.line_segment(
+                    [align.pos_in_rect(&screen), align.pos_in_rect(rect)],
+                    Stroke::new(1.0, Color32::ORANGE),
+                );
+                painter.text(
+                    rect.left_top() - vec2(0.0, 6.0),
+                    Align2::LEFT_BOTTOM,
+                    format!(
+                        "{} × {:.0}   offset ({:.0}, {:.0})",
+                        OverlayAnchor::from_align(align),
+                        rect.width(),
+                        offset.x,
+                        offset.y
+                    ),
+                    FontId::proportional(13.0),
+                    Color32::WHITE,
+                );
+            });
+
+        Area::new(Id::new("layout_toolbar"))
+            .anchor(Align2::CENTER_TOP, vec2(0.0, 20.0))
+            .order(Order::Foreground)
+            .show(ctx, |ui| {
+                Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Drag the box to place the subtitles.");
+                        if ui.add(Button::new("✔ Done")).clicked() {
+                            outcome = LayoutOutcome::Done;
+                        }
+                        if ui.add(Button::new("✖ Cancel")).clicked() {
+                            outcome = LayoutOutcome::Cancelled;
+                        }
+                    });
+                });
+            });
+        outcome
+    }
+
+    /// Writes the anchor closest to the box, its offset and its width back to the settings.
+    pub fn apply(&self, ctx: &Context, settings: &mut SettingsApp) {
+        let Some(rect) = self.rect else {
+            return;
+        };
+        let (align, offset) = placement(ctx.content_rect(), rect);
+        settings.set_anchor(align, offset);
+        settings.subtitle_width = Some(rect.width().round());
+    }
+}
+
+fn keep_inside(rect: Rect, screen: Rect) -> Rect {
+    let dx = (screen.min.x - rect.min.x).max(0.0) + (screen.max.x - rect.max.x).min(0.0);
+    let dy = (screen.min.y - rect.min.y).max(0.0) + (screen.max.y - rect.max.y).min(0.0);
+    rect.translate(Vec2::new(dx, dy))
+}
+
+/// Picks the screen third the box center falls into on each axis, so the subtitles
+/// stay put relative to the nearest edge when the resolution changes.
+fn placement(screen: Rect, rect: Rect) -> (Align2, Vec2) {
+    let third = |value: f32, min: f32, size: f32| match (value - min) / size {
+        t if t < 1.0 / 3.0 => Align::Min,
+        t if t > 2.0 / 3.0 => Align::Max,
+        _ => Align::Center,
+    };
+    let center = rect.center();
+    let align = Align2([
+        third(center.x, screen.min.x, screen.width()),
+        third(center.y, screen.min.y, screen.height()),
+    ]);
+    let offset = align.pos_in_rect(&rect) - align.pos_in_rect(&screen);
+    (align, offset)
+}
@@ -2,5 +2,9 @@ pub mod app;
 pub mod color;
 pub mod draw;
 pub mod font;
+pub mod hotkeys;
+pub mod layout;
+pub mod monitors;
 pub mod settings;
 pub mod state;
+pub mod status;
@@ -0,0 +1,76 @@
+use crate::types::overlay::OverlayMonitor;
+
+/// Displays attached right now, with their bounds in desktop pixels.
+///
+/// Only Windows can list them. Elsewhere the list is empty and the overlay stays on
+/// the display the window is on.
+#[cfg(windows)]
+pub fn attached() -> Vec<OverlayMonitor> {
+    win32::attached()
+}
+
+#[cfg(not(windows))]
+pub fn attached() -> Vec<OverlayMonitor> {
+    Vec::new()
+}
+
+#[cfg(windows)]
+mod win32 {
+    use crate::types::overlay::OverlayMonitor;
+    use windows_sys::Win32::Foundation::{LPARAM, RECT};
+    use windows_sys::Win32::Graphics::Gdi::{
+        EnumDisplayMonitors, GetMonitorInfoW, HDC, HMONITOR, MONITORINFO, MONITORINFOEXW,
+    };
+    use windows_sys::core::BOOL;
+
+    pub fn attached() -> Vec<OverlayMonitor> {
+        let mut monitors = Vec::new();
+        // SAFETY: the callback only runs during the call, while `monitors` is borrowed.
+        let listed = unsafe {
+            EnumDisplayMonitors(
+                std::ptr::null_mut(),
+                std::ptr::null(),
+                Some(collect),
+                &mut monitors as *mut Vec<OverlayMonitor> as LPARAM,
+            )
+        };
+        if listed == 0 {
+            tracing::warn!("Failed to list the attached displays");
+        }
+        monitors
+    }
+
+    unsafe extern "system" fn collect(
+        monitor: HMONITOR,
+        _: HDC,
+        _: *mut RECT,
+        data: LPARAM,
+    ) -> BOOL {
+        // SAFETY: `data` is the vector `attached` passed in.
+        let monitors = unsafe { &mut *(data as *mut Vec<OverlayMonitor>) };
+        let mut info = MONITORINFOEXW::default();
+        info.monitorInfo.cbSize = size_of::<MONITORINFOEXW>() as u32;
+        // SAFETY: `cbSize` tells Windows the extended struct has room for the device name.
+        if unsafe {
+            GetMonitorInfoW(
+                monitor,
+                &mut info as *mut MONITORINFOEXW as *mut MONITORINFO,
+            )
+        } == 0
+        {
+            return 1;
+        }
+        let device = &info.szDevice;
+        let len = device.iter().position(|&c| c == 0).unwrap_or(device.len());
+        let bounds = info.monitorInfo.rcMonitor;
+        monitors.push(OverlayMonitor {
+            name: String::from_utf16_lossy(&device[..len]),
+            position: (bounds.left as f32, bounds.top as f32),
+            size: (
+                (bounds.right - bounds.left) as f32,
+                (bounds.bottom - bounds.top) as f32,
+            ),
+        });
+        1
+    }
+}
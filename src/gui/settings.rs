@@ -1,9 +1,23 @@
+use crate::gui::app::export_transcript;
+use crate::gui::hotkeys::{capture_id, capturing};
+use crate::gui::monitors;
 use crate::gui::state::{PendingState, StateManager};
-use crate::settings::SettingsApp;
+use crate::settings::{MAX_OUTLINE_WIDTH, MIN_SUBTITLE_WIDTH, SettingsApp, SettingsChange};
+use crate::transcription::devices::{DeviceKind, available_devices, available_hosts};
+use crate::transcription::history::{TranscriptHistory, format_unix};
+use crate::transcription::recorder::TranscriptRecorder;
+use crate::types::audio::AudioSource;
+use crate::types::backend::BackendKind;
+use crate::types::hotkeys::{HotkeyAction, HotkeyBindings, KeyBinding};
 use crate::types::languages::LanguageHint;
+use crate::types::overlay::{OverlayAnchor, OverlayMonitor};
+use crate::types::speakers::SpeakerRegistry;
+use crate::types::subtitles::{ConfidenceMode, LanguageDisplay};
+use crate::types::transcript::TranscriptLogFormat;
+use crate::types::translation::TranslationMode;
 use eframe::egui::{
-    self, Button, ComboBox, Context, DragValue, Grid, RichText, ScrollArea, Slider, TextEdit, Ui,
-    vec2,
+    self, Button, ComboBox, Context, DragValue, Event, Grid, Key, RichText, ScrollArea, Slider,
+    TextEdit, Ui, vec2,
 };
 use eframe::epaint::Color32;
 use egui_notify::Toasts;
@@ -13,6 +27,8 @@ pub fn show_settings_window(
     ctx: &Context,
     settings: &mut SettingsApp,
     manager: &mut StateManager,
+    history: &mut TranscriptHistory,
+    recorder: &TranscriptRecorder,
     toasts: &mut Toasts,
 ) {
     ui_bottom_panel(ctx, settings, manager, toasts);
@@ -27,8 +43,14 @@ pub fn show_settings_window(
             ScrollArea::vertical().show(ui, |ui| {
                 ui_log_level(ui, settings);
                 ui_section_api(ui, settings);
-                ui_section_position(ui, ctx, settings);
+                ui_section_connection(ui, settings);
+                ui_section_audio(ui, settings);
+                ui_section_position(ui, ctx, settings, manager);
                 ui_section_appearance(ui, settings);
+                ui_section_speakers(ui, history.speakers_mut());
+                ui_section_hotkeys(ui, ctx, settings);
+                ui_section_recording(ui, settings, recorder, toasts);
+                ui_section_history(ui, history, toasts);
                 ui.allocate_space(vec2(0.0, 60.0));
             });
         });
@@ -52,12 +74,20 @@ fn ui_bottom_panel(
                         .clicked()
                     {
                         match settings.save("soniox.toml") {
-                            Ok(_) => {
-                                toasts
-                                    .success("Settings saved successfully!")
-                                    .duration(Duration::from_secs(3))
-                                    .closable(false);
-                            }
+                            Ok(_) => match settings.validate() {
+                                Ok(_) => {
+                                    toasts
+                                        .success("Settings saved successfully!")
+                                        .duration(Duration::from_secs(3))
+                                        .closable(false);
+                                }
+                                Err(e) => {
+                                    toasts
+                                        .warning(format!("Saved, but can't start yet: {}", e))
+                                        .duration(Duration::from_secs(5))
+                                        .closable(false);
+                                }
+                            },
                             Err(e) => {
                                 toasts
                                     .error(format!("Failed to save: {}", e))
@@ -69,19 +99,53 @@ fn ui_bottom_panel(
                 });
 
                 cols[1].vertical_centered_justified(|ui| {
-                    if ui
-                        .add(Button::new("🚀 Start").min_size(vec2(0.0, 40.0)))
-                        .clicked()
-                    {
-                        manager.switch(PendingState::Overlay);
-                        toasts.info("Starting subtitles overlay...").closable(false);
-                    }
+                    let Some(change) = manager.pending_change(settings) else {
+                        if ui
+                            .add(Button::new("🚀 Start").min_size(vec2(0.0, 40.0)))
+                            .clicked()
+                        {
+                            manager.switch(PendingState::Overlay);
+                            toasts.info("Starting subtitles overlay...").closable(false);
+                        }
+                        return;
+                    };
+                    ui.horizontal(|ui| {
+                        let width = (ui.available_width() - ui.spacing().item_spacing.x) / 2.0;
+                        if ui
+                            .add(Button::new("▶ Resume").min_size(vec2(width, 40.0)))
+                            .on_hover_text(describe_change(change))
+                            .clicked()
+                        {
+                            manager.switch(PendingState::Overlay);
+                        }
+                        if ui
+                            .add(Button::new("⏹ Stop").min_size(vec2(width, 40.0)))
+                            .on_hover_text("End the session and stay in the settings")
+                            .clicked()
+                        {
+                            manager.switch(PendingState::Config);
+                        }
+                    });
                 });
             });
+            if let Some(change) = manager.pending_change(settings)
+                && change != SettingsChange::None
+            {
+                ui.vertical_centered(|ui| ui.weak(describe_change(change)));
+            }
             ui.add_space(10.0);
         });
 }
 
+fn describe_change(change: SettingsChange) -> &'static str {
+    match change {
+        SettingsChange::None => "Nothing changed since the session started",
+        SettingsChange::Visual => "Changes apply without interrupting the session",
+        SettingsChange::Worker => "Resuming reconnects to the service, audio keeps running",
+        SettingsChange::Restart => "Resuming reopens audio capture and starts a new session",
+    }
+}
+
 fn ui_log_level(ui: &mut Ui, settings: &mut SettingsApp) {
     ui.horizontal(|ui| {
         ui.label("Log Level:");
@@ -102,6 +166,42 @@ fn ui_section_api(ui: &mut Ui, settings: &mut SettingsApp) {
             .num_columns(2)
             .spacing([10.0, 10.0])
             .show(ui, |ui| {
+                ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
+                    ui.add(egui::Label::new("Backend:").extend());
+                });
+                ComboBox::from_id_salt("backend")
+                    .selected_text(settings.backend.to_string())
+                    .show_ui(ui, |ui| {
+                        for kind in BackendKind::all() {
+                            ui.selectable_value(&mut settings.backend, *kind, kind.to_string());
+                        }
+                    });
+                ui.end_row();
+
+                ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
+                    ui.add(egui::Label::new("Endpoint:").extend());
+                });
+                ui.add(TextEdit::singleline(&mut settings.endpoint));
+                ui.end_row();
+
+                ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
+                    ui.add(egui::Label::new("Model:").extend());
+                });
+                ui.add(TextEdit::singleline(&mut settings.model));
+                ui.end_row();
+
+                ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
+                    ui.add(egui::Label::new("Query:").extend());
+                });
+                ui_key_value_list(ui, "extra_query", &mut settings.extra_query);
+                ui.end_row();
+
+                ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
+                    ui.add(egui::Label::new("Headers:").extend());
+                });
+                ui_key_value_list(ui, "extra_headers", &mut settings.extra_headers);
+                ui.end_row();
+
                 ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
                     ui.add(egui::Label::new("API Key:").extend());
                 });
@@ -137,11 +237,39 @@ fn ui_section_api(ui: &mut Ui, settings: &mut SettingsApp) {
                 ui.vertical(|ui| {
                     ui.checkbox(&mut settings.enable_translate, "Enable");
                     if settings.enable_translate {
-                        ui_language_searchable_combo(
-                            ui,
-                            "target_lang",
-                            &mut settings.target_language,
-                        );
+                        ui.horizontal(|ui| {
+                            for mode in TranslationMode::all() {
+                                ui.radio_value(
+                                    &mut settings.translation_mode,
+                                    *mode,
+                                    mode.to_string(),
+                                );
+                            }
+                        });
+                        match settings.translation_mode {
+                            TranslationMode::OneWay => ui_language_searchable_combo(
+                                ui,
+                                "target_lang",
+                                &mut settings.target_language,
+                            ),
+                            TranslationMode::TwoWay => {
+                                ui.horizontal(|ui| {
+                                    ui_language_searchable_combo(
+                                        ui,
+                                        "language_a",
+                                        &mut settings.language_a,
+                                    );
+                                    ui.label("⇄");
+                                    ui_language_searchable_combo(
+                                        ui,
+                                        "language_b",
+                                        &mut settings.language_b,
+                                    );
+                                });
+                            }
+                        }
+                        ui.checkbox(&mut settings.show_original, "Show original above")
+                            .on_hover_text("Source text is drawn smaller above the translation");
                     }
                 });
                 ui.end_row();
@@ -153,14 +281,274 @@ fn ui_section_api(ui: &mut Ui, settings: &mut SettingsApp) {
                 ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
                     ui.add(egui::Label::new("Options:").extend());
                 });
-                ui.checkbox(&mut settings.enable_speakers, "Enable Speakers ID");
+                ui.vertical(|ui| {
+                    ui.checkbox(&mut settings.enable_speakers, "Enable Speakers ID");
+                    ui.checkbox(
+                        &mut settings.enable_language_identification,
+                        "Enable Language ID",
+                    );
+                    ui.checkbox(
+                        &mut settings.enable_endpoint_detection,
+                        "Enable Endpoint Detection",
+                    )
+                    .on_hover_text("Finalizes text as soon as a speaker pauses");
+                    if settings.enable_language_identification {
+                        ui.horizontal(|ui| {
+                            ui.label("Show as:");
+                            for display in LanguageDisplay::all() {
+                                ui.radio_value(
+                                    &mut settings.language_display,
+                                    *display,
+                                    display.to_string(),
+                                );
+                            }
+                        });
+                    }
+                });
+                ui.end_row();
+            });
+
+        if let Err(e) = settings.validate() {
+            ui.colored_label(Color32::LIGHT_RED, format!("⚠ {}", e));
+        }
+    });
+}
+
+fn ui_key_value_list(ui: &mut Ui, id_salt: &str, pairs: &mut Vec<(String, String)>) {
+    ui.vertical(|ui| {
+        let mut to_remove = None;
+        for (i, (key, value)) in pairs.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.push_id((id_salt, i), |ui| {
+                    ui.add(
+                        TextEdit::singleline(key)
+                            .hint_text("key")
+                            .desired_width(80.0),
+                    );
+                    ui.add(
+                        TextEdit::singleline(value)
+                            .hint_text("value")
+                            .desired_width(100.0),
+                    );
+                    if ui.button("🗑").clicked() {
+                        to_remove = Some(i);
+                    }
+                });
+            });
+        }
+        if let Some(i) = to_remove {
+            pairs.remove(i);
+        }
+        if ui.button("➕ Add").clicked() {
+            pairs.push((String::new(), String::new()));
+        }
+    });
+}
+
+fn ui_section_connection(ui: &mut Ui, settings: &mut SettingsApp) {
+    ui.collapsing("Reconnection", |ui| {
+        let retry = &mut settings.retry;
+        Grid::new("retry_grid")
+            .num_columns(2)
+            .spacing([10.0, 10.0])
+            .show(ui, |ui| {
+                ui.label("Initial delay (ms):");
+                ui.add(DragValue::new(&mut retry.initial_delay_ms).range(0..=60_000));
+                ui.end_row();
+
+                ui.label("Multiplier:");
+                ui.add(Slider::new(&mut retry.multiplier, 1.0..=4.0));
+                ui.end_row();
+
+                ui.label("Max delay (ms):");
+                ui.add(DragValue::new(&mut retry.max_delay_ms).range(0..=600_000));
+                ui.end_row();
+
+                ui.label("Jitter:");
+                ui.add(Slider::new(&mut retry.jitter, 0.0..=1.0));
+                ui.end_row();
+
+                ui.label("Attempts:");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut retry.retry_forever, "Forever");
+                    ui.add_enabled(
+                        !retry.retry_forever,
+                        DragValue::new(&mut retry.max_attempts).range(1..=100),
+                    );
+                });
+                ui.end_row();
+
+                ui.label("Buffer while offline (s):")
+                    .on_hover_text("Audio captured during a reconnect is replayed afterwards");
+                ui.add(Slider::new(&mut settings.reconnect_buffer_secs, 0.0..=60.0));
+                ui.end_row();
+            });
+    });
+}
+
+/// Enumerating devices is slow on some hosts, so results are kept until the host
+/// changes or the refresh button is pressed.
+#[derive(Clone, Default)]
+struct DeviceCache {
+    host: Option<String>,
+    hosts: Vec<String>,
+    outputs: Vec<String>,
+    inputs: Vec<String>,
+}
+
+fn ui_section_audio(ui: &mut Ui, settings: &mut SettingsApp) {
+    ui.collapsing("Audio Input", |ui| {
+        let cache_id = ui.make_persistent_id("audio_devices_cache");
+        let cached = ui
+            .data(|d| d.get_temp::<DeviceCache>(cache_id))
+            .filter(|c| c.host == settings.audio_host);
+        let cache = cached.unwrap_or_else(|| {
+            let cache = DeviceCache {
+                host: settings.audio_host.clone(),
+                hosts: available_hosts(),
+                outputs: available_devices(settings.audio_host(), DeviceKind::Output),
+                inputs: available_devices(settings.audio_host(), DeviceKind::Input),
+            };
+            ui.data_mut(|d| d.insert_temp(cache_id, cache.clone()));
+            cache
+        });
+
+        Grid::new("audio_grid")
+            .num_columns(2)
+            .spacing([10.0, 10.0])
+            .show(ui, |ui| {
+                ui.label("Source:");
+                ui.horizontal(|ui| {
+                    for source in AudioSource::all() {
+                        ui.radio_value(&mut settings.audio_source, *source, source.to_string());
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Host API:");
+                ui.horizontal(|ui| {
+                    let previous = settings.audio_host.clone();
+                    ComboBox::from_id_salt("audio_host")
+                        .selected_text(settings.audio_host.as_deref().unwrap_or("Default"))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut settings.audio_host, None, "Default");
+                            for host in &cache.hosts {
+                                ui.selectable_value(
+                                    &mut settings.audio_host,
+                                    Some(host.clone()),
+                                    host,
+                                );
+                            }
+                        });
+                    if settings.audio_host != previous {
+                        settings.output_device = None;
+                        settings.input_device = None;
+                    }
+                    if ui.button("🔄").on_hover_text("Refresh devices").clicked() {
+                        ui.data_mut(|d| d.remove_temp::<DeviceCache>(cache_id));
+                    }
+                });
                 ui.end_row();
+
+                let source = settings.audio_source;
+                if matches!(source, AudioSource::SystemOutput | AudioSource::Mixed) {
+                    ui.label("Output device:");
+                    ui_device_combo(
+                        ui,
+                        "output_device",
+                        &mut settings.output_device,
+                        &cache.outputs,
+                    );
+                    ui.end_row();
+                }
+                if matches!(source, AudioSource::Microphone | AudioSource::Mixed) {
+                    ui.label("Microphone:");
+                    ui_device_combo(
+                        ui,
+                        "input_device",
+                        &mut settings.input_device,
+                        &cache.inputs,
+                    );
+                    ui.end_row();
+                }
+                if source == AudioSource::Mixed {
+                    ui.label("System gain:");
+                    ui.add(Slider::new(&mut settings.system_gain, 0.0..=4.0));
+                    ui.end_row();
+
+                    ui.label("Microphone gain:");
+                    ui.add(Slider::new(&mut settings.mic_gain, 0.0..=4.0));
+                    ui.end_row();
+                }
+
+                ui.label("Upload format:")
+                    .on_hover_text("Audio is resampled to this format before it's sent");
+                ui.horizontal(|ui| {
+                    ComboBox::from_id_salt("sample_rate")
+                        .selected_text(format!("{} Hz", settings.sample_rate))
+                        .show_ui(ui, |ui| {
+                            for rate in [8000, 16000, 24000, 44100, 48000] {
+                                ui.selectable_value(
+                                    &mut settings.sample_rate,
+                                    rate,
+                                    format!("{} Hz", rate),
+                                );
+                            }
+                        });
+                    ui.radio_value(&mut settings.channels, 1, "Mono");
+                    ui.radio_value(&mut settings.channels, 2, "Stereo");
+                });
+                ui.end_row();
+
+                ui.label("Voice detection:")
+                    .on_hover_text("Don't upload audio while it's silent");
+                ui.checkbox(&mut settings.enable_vad, "Pause during silence");
+                ui.end_row();
+
+                if settings.enable_vad {
+                    ui.label("Threshold (dBFS):");
+                    ui.add(Slider::new(&mut settings.vad_threshold_db, -80.0..=-10.0));
+                    ui.end_row();
+
+                    ui.label("Hangover (ms):");
+                    ui.add(Slider::new(&mut settings.vad_hangover_ms, 0..=5000));
+                    ui.end_row();
+                }
             });
     });
 }
 
-fn ui_section_position(ui: &mut Ui, ctx: &Context, settings: &mut SettingsApp) {
+fn ui_device_combo(ui: &mut Ui, id_salt: &str, selected: &mut Option<String>, devices: &[String]) {
+    let text = match selected.as_deref() {
+        None => "Default".to_string(),
+        Some(name) if devices.iter().any(|d| d == name) => name.to_string(),
+        Some(name) => format!("{} (missing)", name),
+    };
+    ComboBox::from_id_salt(id_salt)
+        .selected_text(text)
+        .width(180.0)
+        .show_ui(ui, |ui| {
+            ui.selectable_value(selected, None, "Default");
+            for device in devices {
+                ui.selectable_value(selected, Some(device.clone()), device);
+            }
+        });
+}
+
+fn ui_section_position(
+    ui: &mut Ui,
+    ctx: &Context,
+    settings: &mut SettingsApp,
+    manager: &mut StateManager,
+) {
     ui.collapsing("Position", |ui| {
+        if ui
+            .button("🖱 Edit on screen")
+            .on_hover_text("Drag and resize a preview box where the subtitles will appear")
+            .clicked()
+        {
+            manager.switch(PendingState::Layout);
+        }
         Grid::new("pos_grid").spacing([10.0, 10.0]).show(ui, |ui| {
             ui.horizontal(|ui| {
                 ui.add(egui::Label::new("Offset (X, Y):").extend());
@@ -171,6 +559,23 @@ fn ui_section_position(ui: &mut Ui, ctx: &Context, settings: &mut SettingsApp) {
             });
             ui.end_row();
 
+            ui.horizontal(|ui| {
+                let mut fixed = settings.subtitle_width.is_some();
+                if ui.checkbox(&mut fixed, "Fixed width:").changed() {
+                    settings.subtitle_width = fixed.then_some(800.0);
+                }
+                if let Some(width) = &mut settings.subtitle_width {
+                    ui.add(
+                        DragValue::new(width)
+                            .range(MIN_SUBTITLE_WIDTH..=7680.0)
+                            .suffix(" px"),
+                    );
+                } else {
+                    ui.weak("follows the screen");
+                }
+            });
+            ui.end_row();
+
             ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
                 ui.add(egui::Label::new("Snap to:").extend());
             });
@@ -179,48 +584,65 @@ fn ui_section_position(ui: &mut Ui, ctx: &Context, settings: &mut SettingsApp) {
                 Grid::new("snap_buttons")
                     .spacing([5.0, 5.0])
                     .show(ui, |ui| {
-                        let mut btn =
-                            |ui: &mut Ui,
-                             text: &str,
-                             anchor_val: usize,
-                             default_offset: (f32, f32)| {
-                                let is_selected = settings.anchor == anchor_val;
-                                let button = Button::new(RichText::new(text).size(16.0))
-                                    .min_size(vec2(30.0, 30.0));
-
-                                let response = if is_selected {
-                                    ui.add(button.fill(ctx.style().visuals.selection.bg_fill))
-                                } else {
-                                    ui.add(button)
-                                };
-                                if response.clicked() {
-                                    settings.anchor = anchor_val;
-                                    settings.offset = default_offset;
-                                }
-                            };
-
                         let pad = 30.0;
-                        btn(ui, "↖", 0, (pad, pad));
-                        btn(ui, "⬆", 1, (0.0, pad));
-                        btn(ui, "↗", 2, (-pad, pad));
-                        ui.end_row();
-
-                        btn(ui, "←", 3, (pad, 0.0));
-                        btn(ui, "X", 4, (0.0, 0.0));
-                        btn(ui, "→", 5, (-pad, 0.0));
-                        ui.end_row();
-
-                        btn(ui, "↙", 6, (pad, -pad));
-                        btn(ui, "⬇", 7, (0.0, -pad));
-                        btn(ui, "↘", 8, (-pad, -pad));
-                        ui.end_row();
+                        for (i, &anchor) in OverlayAnchor::all().iter().enumerate() {
+                            let button = Button::new(RichText::new(anchor.arrow()).size(16.0))
+                                .min_size(vec2(30.0, 30.0));
+                            let response = if settings.anchor == anchor {
+                                ui.add(button.fill(ctx.style().visuals.selection.bg_fill))
+                            } else {
+                                ui.add(button)
+                            };
+                            if response.on_hover_text(anchor.to_string()).clicked() {
+                                let offset = anchor.default_offset(pad);
+                                settings.set_anchor(anchor.align(), offset);
+                            }
+                            if i % 3 == 2 {
+                                ui.end_row();
+                            }
+                        }
                     });
             });
             ui.end_row();
+
+            ui.horizontal(|ui| {
+                ui_monitor_combo(ui, &mut settings.overlay_monitor);
+            });
+            ui.end_row();
         });
     });
 }
 
+fn ui_monitor_combo(ui: &mut Ui, selected: &mut Option<OverlayMonitor>) {
+    const CURRENT: &str = "The one this window is on";
+    ui.label("Display:");
+    let attached = monitors::attached();
+    if attached.is_empty() && selected.is_none() {
+        ui.weak(CURRENT);
+        return;
+    }
+    let text = match selected.as_ref() {
+        Some(saved) if saved.locate(&attached).is_none() => {
+            format!("{} (not attached)", saved.label())
+        }
+        Some(saved) => saved.to_string(),
+        None => CURRENT.to_owned(),
+    };
+    ComboBox::from_id_salt("overlay_monitor")
+        .selected_text(text)
+        .show_ui(ui, |ui| {
+            ui.selectable_value(selected, None, CURRENT);
+            for monitor in &attached {
+                let chosen = selected
+                    .as_ref()
+                    .is_some_and(|saved| saved.locate(std::slice::from_ref(monitor)).is_some());
+                if ui.selectable_label(chosen, monitor.to_string()).clicked() {
+                    *selected = Some(monitor.clone());
+                }
+            }
+        });
+}
+
 fn ui_language_searchable_combo(
     ui: &mut Ui,
     id_salt: impl std::hash::Hash,
@@ -269,20 +691,92 @@ fn ui_section_appearance(ui: &mut Ui, settings: &mut SettingsApp) {
                 ui.add(Slider::new(&mut settings.max_blocks, 1..=10));
                 ui.end_row();
 
+                ui.label("Block length:")
+                    .on_hover_text("Characters before a line is split at the next word");
+                ui.add(Slider::new(&mut settings.max_block_chars, 40..=400));
+                ui.end_row();
+
                 ui.label("Font size:");
                 ui.add(Slider::new(&mut settings.font_size, 10..=80));
                 ui.end_row();
 
+                ui.label("Line spacing:")
+                    .on_hover_text("Space between subtitle lines");
+                ui.add(Slider::new(&mut settings.line_spacing, 0.0..=32.0));
+                ui.end_row();
+
+                ui.label("Corner radius:");
+                ui.add(Slider::new(&mut settings.corner_radius, 0.0..=32.0));
+                ui.end_row();
+
                 ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
                     ui.add(egui::Label::new("Style:").extend());
                 });
                 Grid::new("font_style").spacing([10.0, 8.0]).show(ui, |ui| {
                     ui.checkbox(&mut settings.enable_background, "Background");
+                    ui.add_enabled_ui(settings.enable_background, |ui| {
+                        rgba_edit_button(ui, &mut settings.background_color);
+                    });
+                    ui.end_row();
+                    ui.checkbox(&mut settings.enable_outline, "Outline");
+                    ui.add_enabled_ui(settings.enable_outline, |ui| {
+                        ui.horizontal(|ui| {
+                            rgba_edit_button(ui, &mut settings.outline_color);
+                            ui.add(
+                                Slider::new(&mut settings.outline_width, 0.5..=MAX_OUTLINE_WIDTH)
+                                    .text("width"),
+                            );
+                        });
+                    });
+                    ui.end_row();
+                    ui.checkbox(&mut settings.enable_shadow, "Shadow");
+                    ui.add_enabled_ui(settings.enable_shadow, |ui| {
+                        ui.horizontal(|ui| {
+                            rgba_edit_button(ui, &mut settings.shadow_color);
+                            ui.add(
+                                DragValue::new(&mut settings.shadow_offset.0)
+                                    .range(-10.0..=10.0)
+                                    .prefix("x: "),
+                            );
+                            ui.add(
+                                DragValue::new(&mut settings.shadow_offset.1)
+                                    .range(-10.0..=10.0)
+                                    .prefix("y: "),
+                            );
+                        });
+                    });
                     ui.end_row();
                     ui.checkbox(&mut settings.enable_high_priority, "Always on top");
                     ui.end_row();
                 });
                 ui.end_row();
+
+                ui.label("Low confidence:")
+                    .on_hover_text("How words the recognizer is unsure about are drawn");
+                ComboBox::from_id_salt("confidence_mode")
+                    .selected_text(settings.confidence_mode.to_string())
+                    .show_ui(ui, |ui| {
+                        for mode in ConfidenceMode::all() {
+                            ui.selectable_value(
+                                &mut settings.confidence_mode,
+                                *mode,
+                                mode.to_string(),
+                            );
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Threshold:");
+                ui.add_enabled(
+                    settings.confidence_mode != ConfidenceMode::Off,
+                    Slider::new(&mut settings.confidence_threshold, 0.0..=1.0),
+                );
+                ui.end_row();
+
+                ui.label("Hide below:")
+                    .on_hover_text("Words under this confidence aren't shown at all");
+                ui.add(Slider::new(&mut settings.confidence_floor, 0.0..=1.0));
+                ui.end_row();
             });
 
         ui.separator();
@@ -343,3 +837,270 @@ fn ui_section_appearance(ui: &mut Ui, settings: &mut SettingsApp) {
             });
     });
 }
+
+fn rgba_edit_button(ui: &mut Ui, color: &mut (u8, u8, u8, u8)) {
+    let mut rgba = [color.0, color.1, color.2, color.3];
+    if ui.color_edit_button_srgba_unmultiplied(&mut rgba).changed() {
+        *color = (rgba[0], rgba[1], rgba[2], rgba[3]);
+    }
+}
+
+fn ui_section_speakers(ui: &mut Ui, speakers: &mut SpeakerRegistry) {
+    ui.collapsing("Speakers", |ui| {
+        if speakers.is_empty() {
+            ui.weak("Speakers appear here once diarization detects them.");
+        }
+        let mut to_remove = None;
+        Grid::new("speakers_grid")
+            .num_columns(4)
+            .spacing([10.0, 8.0])
+            .show(ui, |ui| {
+                for (id, profile) in speakers.iter_mut() {
+                    ui.label(format!("{}:", id));
+                    ui.add(
+                        TextEdit::singleline(&mut profile.name)
+                            .hint_text(format!("Speaker {}", id))
+                            .desired_width(140.0),
+                    );
+                    let mut color = [profile.color.0, profile.color.1, profile.color.2];
+                    if ui.color_edit_button_srgb(&mut color).changed() {
+                        profile.color = (color[0], color[1], color[2]);
+                    }
+                    if ui.button("🗑").clicked() {
+                        to_remove = Some(id.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(id) = to_remove {
+            speakers.remove(&id);
+        }
+
+        ui.horizontal(|ui| {
+            let id = ui.id().with("new_speaker");
+            let mut new_id = ui.data(|d| d.get_temp::<String>(id)).unwrap_or_default();
+            ui.add(
+                TextEdit::singleline(&mut new_id)
+                    .hint_text("id")
+                    .desired_width(60.0),
+            );
+            if ui.button("➕ Add").clicked() && !new_id.trim().is_empty() {
+                speakers.register(new_id.trim());
+                new_id.clear();
+            }
+            ui.data_mut(|d| d.insert_temp(id, new_id));
+
+            if ui
+                .add_enabled(!speakers.is_empty(), Button::new("🧹 Clear"))
+                .clicked()
+            {
+                speakers.clear();
+            }
+        });
+    });
+}
+
+fn ui_section_hotkeys(ui: &mut Ui, ctx: &Context, settings: &mut SettingsApp) {
+    ui.collapsing("Hotkeys", |ui| {
+        let editing = capturing(ctx);
+        if let Some(action) = editing {
+            let pressed = ui.input(|i| {
+                i.events.iter().find_map(|event| match event {
+                    Event::Key {
+                        key,
+                        pressed: true,
+                        modifiers,
+                        ..
+                    } => Some(KeyBinding::from_input(*key, *modifiers)),
+                    _ => None,
+                })
+            });
+            if let Some(binding) = pressed {
+                if binding != KeyBinding::new(Key::Escape) {
+                    *settings.hotkeys.get_mut(action) = Some(binding);
+                }
+                ctx.data_mut(|d| d.remove::<HotkeyAction>(capture_id()));
+            }
+        }
+
+        Grid::new("hotkeys_grid")
+            .num_columns(3)
+            .spacing([10.0, 8.0])
+            .show(ui, |ui| {
+                for &action in HotkeyAction::all() {
+                    ui.label(format!("{}:", action));
+                    let binding = settings.hotkeys.get(action);
+                    let text = match binding {
+                        _ if editing == Some(action) => "Press a key... (Esc to cancel)".into(),
+                        Some(binding) => binding.to_string(),
+                        None => "Not set".into(),
+                    };
+                    let conflict = binding.and_then(|b| settings.hotkeys.conflict(action, b));
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add(Button::new(text).min_size(vec2(140.0, 0.0)))
+                            .on_hover_text("Click and press the new key combination")
+                            .clicked()
+                        {
+                            ctx.data_mut(|d| d.insert_temp(capture_id(), action));
+                        }
+                        if let Some(other) = conflict {
+                            ui.colored_label(Color32::ORANGE, "⚠")
+                                .on_hover_text(format!("Also bound to \"{}\"", other));
+                        }
+                    });
+                    if ui
+                        .add_enabled(binding.is_some(), Button::new("✖"))
+                        .on_hover_text("Disable this hotkey")
+                        .clicked()
+                    {
+                        *settings.hotkeys.get_mut(action) = None;
+                    }
+                    ui.end_row();
+                }
+            });
+
+        ui.horizontal(|ui| {
+            if ui.button("↺ Reset to defaults").clicked() {
+                settings.hotkeys = HotkeyBindings::default();
+            }
+        });
+        if cfg!(windows) {
+            ui.weak("Hotkeys work from any application while a session runs. A combination another application already uses is skipped.");
+        } else {
+            ui.weak("On this platform hotkeys only work while the window is focused.");
+        }
+    });
+}
+
+fn ui_section_recording(
+    ui: &mut Ui,
+    settings: &mut SettingsApp,
+    recorder: &TranscriptRecorder,
+    toasts: &mut Toasts,
+) {
+    ui.collapsing("Recording", |ui| {
+        Grid::new("recording_grid")
+            .num_columns(2)
+            .spacing([10.0, 10.0])
+            .show(ui, |ui| {
+                ui.label("Record:");
+                ui.checkbox(
+                    &mut settings.enable_recording,
+                    "Save subtitles when stopped",
+                )
+                .on_hover_text("Final transcript is written to the folder below on exit");
+                ui.end_row();
+
+                ui.label("Folder:");
+                ui.add_enabled(
+                    settings.enable_recording,
+                    TextEdit::singleline(&mut settings.export_dir),
+                );
+                ui.end_row();
+
+                ui.label("Formats:");
+                ui.add_enabled_ui(settings.enable_recording, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut settings.export_srt, "SubRip (.srt)");
+                        ui.checkbox(&mut settings.export_vtt, "WebVTT (.vtt)");
+                    });
+                });
+                ui.end_row();
+
+                ui.label("Live log:");
+                ui.checkbox(&mut settings.enable_transcript_log, "Append utterances")
+                    .on_hover_text("A new file is started for every session");
+                ui.end_row();
+
+                ui.label("Log file:");
+                ui.add_enabled(
+                    settings.enable_transcript_log,
+                    TextEdit::singleline(&mut settings.transcript_log_path),
+                );
+                ui.end_row();
+
+                ui.label("Log format:");
+                ui.add_enabled_ui(settings.enable_transcript_log, |ui| {
+                    ui.horizontal(|ui| {
+                        for format in TranscriptLogFormat::all() {
+                            ui.radio_value(
+                                &mut settings.transcript_log_format,
+                                *format,
+                                format.to_string(),
+                            );
+                        }
+                    });
+                });
+                ui.end_row();
+            });
+
+        let label = format!("📤 Export last session ({} cues)", recorder.cues().len());
+        if ui
+            .add_enabled(!recorder.is_empty(), Button::new(label))
+            .clicked()
+        {
+            export_transcript(recorder, settings, toasts);
+        }
+    });
+}
+
+fn ui_section_history(ui: &mut Ui, history: &TranscriptHistory, toasts: &mut Toasts) {
+    ui.collapsing("History", |ui| {
+        let id = ui.id().with("history_query");
+        let mut query = ui.data(|d| d.get_temp::<String>(id)).unwrap_or_default();
+        ui.horizontal(|ui| {
+            ui.label("🔍");
+            ui.add(
+                TextEdit::singleline(&mut query)
+                    .hint_text("Search")
+                    .desired_width(180.0),
+            );
+            if ui.button("📋 Copy all").clicked() {
+                let text = history
+                    .search(&query)
+                    .map(|e| history.describe(e))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                ui.ctx().copy_text(text);
+                toasts
+                    .info("History copied to clipboard")
+                    .duration(Duration::from_secs(3))
+                    .closable(false);
+            }
+        });
+
+        let found: Vec<_> = history.search(&query).collect();
+        ui.weak(format!(
+            "{} of {} entries, {} sessions",
+            found.len(),
+            history.entries().len(),
+            history.sessions()
+        ));
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Body) + 4.0;
+        ScrollArea::vertical()
+            .id_salt("history_scroll")
+            .max_height(260.0)
+            .stick_to_bottom(true)
+            .show_rows(ui, row_height, found.len(), |ui, rows| {
+                for entry in &found[rows] {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("📋").on_hover_text("Copy").clicked() {
+                            ui.ctx().copy_text(history.describe(entry));
+                        }
+                        let time = format_unix(entry.timestamp);
+                        if entry.session == history.session() {
+                            ui.label(RichText::new(time).weak().strong());
+                        } else {
+                            ui.weak(time);
+                        }
+                        ui.add(egui::Label::new(history.describe(entry)).truncate())
+                            .on_hover_text(entry.text.trim());
+                    });
+                }
+            });
+
+        ui.data_mut(|d| d.insert_temp(id, query));
+    });
+}
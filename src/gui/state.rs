@@ -1,5 +1,6 @@
 use crate::errors::SonioxLiveErrors;
-use crate::settings::SettingsApp;
+use crate::gui::monitors;
+use crate::settings::{SettingsApp, SettingsChange};
 use crate::transcription::service::TranscriptionService;
 use crate::transcription::store::TranscriptionStore;
 use eframe::egui::{Context, ViewportCommand, WindowLevel};
@@ -7,17 +8,26 @@ use eframe::egui::{Context, ViewportCommand, WindowLevel};
 pub struct StateManager {
     app_state: AppState,
     pending_state: Option<PendingState>,
+    /// Settings the running service was started or last restarted with.
+    applied: Option<SettingsApp>,
 }
 
 #[derive(Clone, Copy)]
 pub enum PendingState {
     Config,
+    /// The settings window on top of a running session, which keeps transcribing.
+    Settings,
     Overlay,
+    /// Full-screen placement editor. Unlike the overlay it takes mouse input.
+    Layout,
 }
 
 pub enum AppState {
     Config,
+    Settings(TranscriptionService),
     Overlay(TranscriptionService),
+    /// Keeps the session running when the editor is opened from the settings of one.
+    Layout(Option<TranscriptionService>),
 }
 
 impl Default for StateManager {
@@ -31,6 +41,7 @@ impl StateManager {
         Self {
             app_state: AppState::Config,
             pending_state: Some(PendingState::Config),
+            applied: None,
         }
     }
 
@@ -38,27 +49,109 @@ impl StateManager {
         self.pending_state = Some(new_state);
     }
 
+    /// Switches to the pending state and returns what it took to get the session there.
     pub fn resolve(
         &mut self,
         ctx: &Context,
         store: &mut TranscriptionStore,
         settings: &SettingsApp,
-    ) -> Result<(), SonioxLiveErrors> {
+    ) -> Result<SettingsChange, SonioxLiveErrors> {
         let Some(resolved) = self.pending_state.take() else {
-            return Ok(());
+            return Ok(SettingsChange::None);
         };
 
-        resolved.apply_window_state(ctx, settings.enable_high_priority());
-        match resolved {
-            PendingState::Config => self.app_state = AppState::Config,
-            PendingState::Overlay => {
-                let ctx = ctx.clone();
-                let service = TranscriptionService::start(ctx, settings)?;
-                store.resize(settings.max_blocks);
+        let result = match resolved {
+            PendingState::Config => {
+                self.app_state = AppState::Config;
+                self.applied = None;
+                Ok(SettingsChange::None)
+            }
+            PendingState::Settings => {
+                self.app_state = match self.take_service() {
+                    Some(service) => AppState::Settings(service),
+                    None => AppState::Config,
+                };
+                Ok(SettingsChange::None)
+            }
+            PendingState::Overlay => self.show_overlay(ctx, store, settings),
+            PendingState::Layout => {
+                self.app_state = AppState::Layout(self.take_service());
+                Ok(SettingsChange::None)
+            }
+        };
+
+        let shown = match self.app_state {
+            AppState::Overlay(_) => PendingState::Overlay,
+            AppState::Layout(_) => PendingState::Layout,
+            AppState::Config | AppState::Settings(_) => PendingState::Config,
+        };
+        shown.apply_window_state(ctx, settings);
+        result
+    }
+
+    /// Resumes a running session when the edits allow it, otherwise starts a new one.
+    fn show_overlay(
+        &mut self,
+        ctx: &Context,
+        store: &mut TranscriptionStore,
+        settings: &SettingsApp,
+    ) -> Result<SettingsChange, SonioxLiveErrors> {
+        let change = match (self.has_session(), &self.applied) {
+            (true, Some(applied)) => applied.diff(settings),
+            _ => SettingsChange::Restart,
+        };
+        tracing::debug!("Applying settings change: {:?}", change);
+
+        if change == SettingsChange::Restart {
+            // The old capture has to be released before the devices are opened again.
+            self.app_state = AppState::Config;
+            self.applied = None;
+            let service = TranscriptionService::start(ctx.clone(), settings)?;
+            self.app_state = AppState::Overlay(service);
+        } else {
+            if change == SettingsChange::Worker
+                && let Some(service) = self.service_mut()
+            {
+                service.restart_worker(settings)?;
+            }
+            if let Some(service) = self.take_service() {
                 self.app_state = AppState::Overlay(service);
             }
         }
-        Ok(())
+        apply_store_settings(store, settings);
+        self.applied = Some(settings.clone());
+        Ok(change)
+    }
+
+    /// What resuming the running session with `settings` would take, if there is one.
+    pub fn pending_change(&self, settings: &SettingsApp) -> Option<SettingsChange> {
+        match (&self.app_state, &self.applied) {
+            (AppState::Settings(_), Some(applied)) => Some(applied.diff(settings)),
+            _ => None,
+        }
+    }
+
+    /// Whether a transcription service is running, visible or not.
+    pub fn has_session(&self) -> bool {
+        !matches!(self.app_state, AppState::Config | AppState::Layout(None))
+    }
+
+    pub fn service_mut(&mut self) -> Option<&mut TranscriptionService> {
+        match &mut self.app_state {
+            AppState::Settings(service)
+            | AppState::Overlay(service)
+            | AppState::Layout(Some(service)) => Some(service),
+            AppState::Config | AppState::Layout(None) => None,
+        }
+    }
+
+    fn take_service(&mut self) -> Option<TranscriptionService> {
+        match std::mem::replace(&mut self.app_state, AppState::Config) {
+            AppState::Settings(service)
+            | AppState::Overlay(service)
+            | AppState::Layout(Some(service)) => Some(service),
+            AppState::Config | AppState::Layout(None) => None,
+        }
     }
 
     pub fn app_state(&self) -> &AppState {
@@ -70,26 +163,70 @@ impl StateManager {
     }
 }
 
+/// The store part of the settings is cheap to apply, so it's applied on every frame
+/// while a session is open and edits show up right away.
+pub fn apply_store_settings(store: &mut TranscriptionStore, settings: &SettingsApp) {
+    store.resize(settings.max_blocks);
+    store.set_show_original(settings.show_original());
+    store.set_max_chars(settings.max_block_chars());
+}
+
 impl PendingState {
-    pub fn apply_window_state(&self, ctx: &Context, enable_high_priority: bool) {
+    pub fn apply_window_state(&self, ctx: &Context, settings: &SettingsApp) {
         match self {
-            Self::Config => {
+            Self::Config | Self::Settings => {
                 ctx.send_viewport_cmd(ViewportCommand::Decorations(true));
                 ctx.send_viewport_cmd(ViewportCommand::Transparent(false));
                 ctx.send_viewport_cmd(ViewportCommand::MousePassthrough(false));
                 ctx.send_viewport_cmd(ViewportCommand::Resizable(false));
                 ctx.send_viewport_cmd(ViewportCommand::WindowLevel(WindowLevel::Normal));
+                ctx.send_viewport_cmd(ViewportCommand::Maximized(false));
+                ctx.send_viewport_cmd(ViewportCommand::Focus);
             }
             Self::Overlay => {
                 ctx.send_viewport_cmd(ViewportCommand::Decorations(false));
                 ctx.send_viewport_cmd(ViewportCommand::Transparent(true));
                 ctx.send_viewport_cmd(ViewportCommand::MousePassthrough(true));
+                move_to_monitor(ctx, settings);
                 ctx.send_viewport_cmd(ViewportCommand::Maximized(true));
 
-                if enable_high_priority {
+                if settings.enable_high_priority() {
                     ctx.send_viewport_cmd(ViewportCommand::WindowLevel(WindowLevel::AlwaysOnTop));
                 }
             }
+            Self::Layout => {
+                ctx.send_viewport_cmd(ViewportCommand::Decorations(false));
+                ctx.send_viewport_cmd(ViewportCommand::Transparent(true));
+                ctx.send_viewport_cmd(ViewportCommand::MousePassthrough(false));
+                move_to_monitor(ctx, settings);
+                ctx.send_viewport_cmd(ViewportCommand::Maximized(true));
+                ctx.send_viewport_cmd(ViewportCommand::WindowLevel(WindowLevel::AlwaysOnTop));
+                ctx.send_viewport_cmd(ViewportCommand::Focus);
+            }
         }
     }
 }
+
+/// Maximizing fills the display the window is on, so it's moved onto the chosen one first.
+/// A display that is no longer attached leaves the window where it is.
+fn move_to_monitor(ctx: &Context, settings: &SettingsApp) {
+    let Some(saved) = settings.overlay_monitor() else {
+        return;
+    };
+    let attached = monitors::attached();
+    let Some(monitor) = saved.locate(&attached) else {
+        tracing::warn!(
+            "Display {} is not attached, the overlay opens on the current one",
+            saved.label()
+        );
+        return;
+    };
+    // Desktop pixels, while egui positions the window in points of its current display.
+    let pixels_per_point = ctx
+        .input(|i| i.viewport().native_pixels_per_point)
+        .unwrap_or(1.0);
+    ctx.send_viewport_cmd(ViewportCommand::Maximized(false));
+    ctx.send_viewport_cmd(ViewportCommand::OuterPosition(
+        monitor.rect().min / pixels_per_point,
+    ));
+}
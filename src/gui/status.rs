@@ -0,0 +1,78 @@
+use eframe::egui::{Color32, Context, RichText, Ui};
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
+pub struct OverlayStatus {
+    reconnect: Option<(u32, Instant)>,
+    listening: Option<bool>,
+    paused: bool,
+}
+
+impl OverlayStatus {
+    pub fn set_reconnecting(&mut self, attempt: u32, delay: Duration) {
+        self.reconnect = Some((attempt, Instant::now() + delay));
+    }
+
+    pub fn clear_reconnecting(&mut self) {
+        self.reconnect = None;
+    }
+
+    pub fn set_listening(&mut self, listening: bool) {
+        self.listening = Some(listening);
+    }
+
+    pub fn listening(&self) -> Option<bool> {
+        self.listening
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn message(&self) -> Option<String> {
+        let (attempt, retry_at) = self.reconnect?;
+        let remaining = retry_at.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Some(format!("Reconnecting... (attempt {})", attempt));
+        }
+        Some(format!(
+            "Reconnecting in {} s (attempt {})",
+            remaining.as_secs_f32().ceil() as u64,
+            attempt
+        ))
+    }
+
+    pub fn schedule(&self, ctx: &Context) {
+        if self.reconnect.is_some() {
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+    }
+}
+
+pub fn draw_status(ui: &mut Ui, status: &OverlayStatus, font_size: f32, text_color: Color32) {
+    let size = (font_size * 0.7).max(10.0);
+    if status.paused() {
+        ui.label(RichText::new("⏸ Paused").size(size).color(Color32::GRAY));
+    } else if let Some(listening) = status.listening() {
+        let (text, color) = if listening {
+            ("🎙 Listening", text_color)
+        } else {
+            ("💤 Silent", Color32::GRAY)
+        };
+        ui.label(RichText::new(text).size(size).color(color));
+    }
+
+    let Some(message) = status.message() else {
+        return;
+    };
+    ui.label(
+        RichText::new(message)
+            .size(size)
+            .color(text_color)
+            .background_color(Color32::from_black_alpha(155)),
+    );
+}
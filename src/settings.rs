@@ -1,47 +1,171 @@
 use crate::errors::SonioxLiveErrors;
+use crate::soniox::retry::RetryPolicy;
+use crate::soniox::{DEFAULT_MODEL, DEFAULT_URL};
+use crate::types::audio::AudioSource;
+use crate::types::backend::BackendKind;
+use crate::types::hotkeys::HotkeyBindings;
 use crate::types::languages::LanguageHint;
-use eframe::egui::{Align2, Color32, Vec2, vec2};
+use crate::types::overlay::{OverlayAnchor, OverlayMonitor};
+use crate::types::subtitles::{ConfidenceMode, ConfidenceStyle, LanguageDisplay};
+use crate::types::transcript::TranscriptLogFormat;
+use crate::types::translation::TranslationMode;
+use eframe::egui::{Align2, Color32, Stroke, Vec2, vec2};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
 use tracing_subscriber::filter::LevelFilter;
+use tungstenite::http::{HeaderName, HeaderValue};
 
-#[derive(Deserialize, Serialize, Clone)]
+pub const MIN_SUBTITLE_WIDTH: f32 = 120.0;
+pub const MAX_OUTLINE_WIDTH: f32 = 6.0;
+
+/// What has to be redone for edited settings to reach a running session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SettingsChange {
+    None,
+    /// Only drawing and the subtitle store are affected.
+    Visual,
+    /// The transcription request changed, the worker reconnects with a new one.
+    Worker,
+    /// Audio capture has to be reopened, so the whole service starts over.
+    Restart,
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
 pub struct SettingsApp {
+    pub(crate) backend: BackendKind,
+    pub(crate) endpoint: String,
+    pub(crate) model: String,
+    pub(crate) extra_query: Vec<(String, String)>,
+    pub(crate) extra_headers: Vec<(String, String)>,
+    pub(crate) retry: RetryPolicy,
+    pub(crate) reconnect_buffer_secs: f32,
+    pub(crate) audio_source: AudioSource,
+    pub(crate) audio_host: Option<String>,
+    pub(crate) output_device: Option<String>,
+    pub(crate) input_device: Option<String>,
+    pub(crate) system_gain: f32,
+    pub(crate) mic_gain: f32,
+    pub(crate) sample_rate: u32,
+    pub(crate) channels: u16,
+    pub(crate) enable_vad: bool,
+    pub(crate) vad_threshold_db: f32,
+    pub(crate) vad_hangover_ms: u32,
+    pub(crate) enable_recording: bool,
+    pub(crate) export_dir: String,
+    pub(crate) export_srt: bool,
+    pub(crate) export_vtt: bool,
+    pub(crate) history_file: String,
+    pub(crate) enable_transcript_log: bool,
+    pub(crate) transcript_log_path: String,
+    pub(crate) transcript_log_format: TranscriptLogFormat,
     pub(crate) language_hints: Vec<LanguageHint>,
     pub(crate) context: String,
     pub(crate) api_key: String,
     pub(crate) target_language: LanguageHint,
     pub(crate) enable_translate: bool,
+    pub(crate) translation_mode: TranslationMode,
+    pub(crate) language_a: LanguageHint,
+    pub(crate) language_b: LanguageHint,
+    pub(crate) show_original: bool,
     pub(crate) enable_high_priority: bool,
     pub(crate) enable_speakers: bool,
+    pub(crate) enable_language_identification: bool,
+    pub(crate) enable_endpoint_detection: bool,
+    pub(crate) language_display: LanguageDisplay,
     pub(crate) enable_background: bool,
     pub(crate) level: String, // maybe to make it an enum
     pub(crate) offset: (f32, f32),
-    pub(crate) anchor: usize,
+    pub(crate) anchor: OverlayAnchor,
+    pub(crate) overlay_monitor: Option<OverlayMonitor>,
+    pub(crate) subtitle_width: Option<f32>,
     pub(crate) font_size: usize,
     pub(crate) text_color: (u8, u8, u8),
+    pub(crate) background_color: (u8, u8, u8, u8),
+    pub(crate) corner_radius: f32,
+    pub(crate) line_spacing: f32,
+    pub(crate) enable_outline: bool,
+    pub(crate) outline_width: f32,
+    pub(crate) outline_color: (u8, u8, u8, u8),
+    pub(crate) enable_shadow: bool,
+    pub(crate) shadow_offset: (f32, f32),
+    pub(crate) shadow_color: (u8, u8, u8, u8),
     pub(crate) max_blocks: usize,
+    pub(crate) max_block_chars: usize,
+    pub(crate) confidence_mode: ConfidenceMode,
+    pub(crate) confidence_threshold: f32,
+    pub(crate) confidence_floor: f32,
+    pub(crate) hotkeys: HotkeyBindings,
 }
 
 impl Default for SettingsApp {
     fn default() -> Self {
         Self {
+            backend: BackendKind::default(),
+            endpoint: DEFAULT_URL.into(),
+            model: DEFAULT_MODEL.into(),
+            extra_query: Vec::new(),
+            extra_headers: Vec::new(),
+            retry: RetryPolicy::default(),
+            reconnect_buffer_secs: 10.0,
+            audio_source: AudioSource::default(),
+            audio_host: None,
+            output_device: None,
+            input_device: None,
+            system_gain: 1.0,
+            mic_gain: 1.0,
+            sample_rate: 16000,
+            channels: 1,
+            enable_vad: false,
+            vad_threshold_db: -50.0,
+            vad_hangover_ms: 1000,
+            enable_recording: false,
+            export_dir: "transcripts".into(),
+            export_srt: true,
+            export_vtt: false,
+            history_file: "history.jsonl".into(),
+            enable_transcript_log: false,
+            transcript_log_path: "transcripts/transcript".into(),
+            transcript_log_format: TranscriptLogFormat::default(),
             language_hints: vec![LanguageHint::default()],
             context: String::from("some kind context"),
             api_key: String::new(),
             target_language: LanguageHint::default(),
             enable_translate: false,
+            translation_mode: TranslationMode::default(),
+            language_a: LanguageHint::English,
+            language_b: LanguageHint::Spanish,
+            show_original: false,
             enable_high_priority: true,
             enable_speakers: true,
+            enable_language_identification: false,
+            enable_endpoint_detection: true,
+            language_display: LanguageDisplay::default(),
             enable_background: true,
             level: "info".into(),
             offset: (0.0, -30.0),
-            anchor: 7,
+            anchor: OverlayAnchor::default(),
+            overlay_monitor: None,
+            subtitle_width: None,
             font_size: 18,
             text_color: (255, 255, 0), // yellow
+            background_color: (0, 0, 0, 155),
+            corner_radius: 12.0,
+            line_spacing: 4.0,
+            enable_outline: false,
+            outline_width: 1.5,
+            outline_color: (0, 0, 0, 255),
+            enable_shadow: false,
+            shadow_offset: (2.0, 2.0),
+            shadow_color: (0, 0, 0, 160),
             max_blocks: 3,
+            max_block_chars: 200,
+            confidence_mode: ConfidenceMode::default(),
+            confidence_threshold: 0.6,
+            confidence_floor: 0.0,
+            hotkeys: HotkeyBindings::default(),
         }
     }
 }
@@ -61,6 +185,110 @@ impl SettingsApp {
         Ok(s)
     }
 
+    pub fn backend(&self) -> BackendKind {
+        self.backend
+    }
+
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    pub fn model(&self) -> Arc<str> {
+        Arc::from(&*self.model)
+    }
+
+    pub fn extra_query(&self) -> &[(String, String)] {
+        &self.extra_query
+    }
+
+    pub fn extra_headers(&self) -> &[(String, String)] {
+        &self.extra_headers
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    pub fn reconnect_buffer_secs(&self) -> f32 {
+        self.reconnect_buffer_secs
+    }
+
+    pub fn audio_source(&self) -> AudioSource {
+        self.audio_source
+    }
+
+    pub fn audio_host(&self) -> Option<&str> {
+        self.audio_host.as_deref()
+    }
+
+    pub fn output_device(&self) -> Option<&str> {
+        self.output_device.as_deref()
+    }
+
+    pub fn input_device(&self) -> Option<&str> {
+        self.input_device.as_deref()
+    }
+
+    pub fn system_gain(&self) -> f32 {
+        self.system_gain
+    }
+
+    pub fn mic_gain(&self) -> f32 {
+        self.mic_gain
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    pub fn enable_vad(&self) -> bool {
+        self.enable_vad
+    }
+
+    pub fn vad_threshold_db(&self) -> f32 {
+        self.vad_threshold_db
+    }
+
+    pub fn vad_hangover_ms(&self) -> u32 {
+        self.vad_hangover_ms
+    }
+
+    pub fn enable_recording(&self) -> bool {
+        self.enable_recording
+    }
+
+    pub fn export_dir(&self) -> &str {
+        &self.export_dir
+    }
+
+    pub fn export_srt(&self) -> bool {
+        self.export_srt
+    }
+
+    pub fn export_vtt(&self) -> bool {
+        self.export_vtt
+    }
+
+    pub fn history_file(&self) -> Option<&str> {
+        Some(self.history_file.trim()).filter(|p| !p.is_empty())
+    }
+
+    pub fn enable_transcript_log(&self) -> bool {
+        self.enable_transcript_log
+    }
+
+    pub fn transcript_log_path(&self) -> &str {
+        &self.transcript_log_path
+    }
+
+    pub fn transcript_log_format(&self) -> TranscriptLogFormat {
+        self.transcript_log_format
+    }
+
     pub fn language_hints(&self) -> Arc<[LanguageHint]> {
         Arc::from(&*self.language_hints)
     }
@@ -81,10 +309,43 @@ impl SettingsApp {
         self.enable_speakers
     }
 
+    pub fn enable_language_identification(&self) -> bool {
+        self.enable_language_identification
+    }
+
+    pub fn enable_endpoint_detection(&self) -> bool {
+        self.enable_endpoint_detection
+    }
+
+    /// Tags and colors only make sense while the API reports languages.
+    pub fn language_display(&self) -> LanguageDisplay {
+        if self.enable_language_identification {
+            self.language_display
+        } else {
+            LanguageDisplay::Off
+        }
+    }
+
     pub fn enable_translate(&self) -> bool {
         self.enable_translate
     }
 
+    pub fn translation_mode(&self) -> TranslationMode {
+        self.translation_mode
+    }
+
+    pub fn language_a(&self) -> LanguageHint {
+        self.language_a
+    }
+
+    pub fn language_b(&self) -> LanguageHint {
+        self.language_b
+    }
+
+    pub fn show_original(&self) -> bool {
+        self.enable_translate && self.show_original
+    }
+
     pub fn enable_high_priority(&self) -> bool {
         self.enable_high_priority
     }
@@ -93,10 +354,38 @@ impl SettingsApp {
         self.font_size as f32
     }
 
+    pub fn anchor(&self) -> OverlayAnchor {
+        self.anchor
+    }
+
+    pub fn overlay_monitor(&self) -> Option<&OverlayMonitor> {
+        self.overlay_monitor.as_ref()
+    }
+
+    pub fn subtitle_width(&self) -> Option<f32> {
+        self.subtitle_width
+    }
+
     pub fn max_blocks(&self) -> usize {
         self.max_blocks
     }
 
+    pub fn max_block_chars(&self) -> usize {
+        self.max_block_chars
+    }
+
+    pub fn hotkeys(&self) -> &HotkeyBindings {
+        &self.hotkeys
+    }
+
+    pub fn confidence_style(&self) -> ConfidenceStyle {
+        ConfidenceStyle {
+            mode: self.confidence_mode,
+            threshold: self.confidence_threshold,
+            floor: self.confidence_floor,
+        }
+    }
+
     pub fn level(&self) -> Result<LevelFilter, SonioxLiveErrors> {
         LevelFilter::from_str(&self.level).map_err(|_| {
             SonioxLiveErrors::from(
@@ -105,33 +394,185 @@ impl SettingsApp {
         })
     }
 
+    pub fn validate(&self) -> Result<(), SonioxLiveErrors> {
+        let endpoint = self.endpoint.trim();
+        if !endpoint.starts_with("ws://") && !endpoint.starts_with("wss://") {
+            return Err(SonioxLiveErrors::from(
+                "field `endpoint` isn't valid. it must start with `ws://` or `wss://`",
+            ));
+        }
+        if self.model.trim().is_empty() {
+            return Err(SonioxLiveErrors::from("field `model` mustn't be empty"));
+        }
+        if self.extra_query.iter().any(|(k, _)| k.trim().is_empty()) {
+            return Err(SonioxLiveErrors::from(
+                "field `extra_query` contains an empty key",
+            ));
+        }
+        for (name, value) in &self.extra_headers {
+            if HeaderName::from_bytes(name.as_bytes()).is_err() {
+                return Err(SonioxLiveErrors::from(format!(
+                    "header name `{}` isn't valid",
+                    name
+                )));
+            }
+            if HeaderValue::from_str(value).is_err() {
+                return Err(SonioxLiveErrors::from(format!(
+                    "value of header `{}` isn't valid",
+                    name
+                )));
+            }
+        }
+        if !(8000..=48000).contains(&self.sample_rate) {
+            return Err(SonioxLiveErrors::from(
+                "field `sample_rate` must be between 8000 and 48000",
+            ));
+        }
+        if !(1..=2).contains(&self.channels) {
+            return Err(SonioxLiveErrors::from("field `channels` must be 1 or 2"));
+        }
+        if self.enable_translate
+            && self.translation_mode == TranslationMode::TwoWay
+            && self.language_a == self.language_b
+        {
+            return Err(SonioxLiveErrors::from(
+                "fields `language_a` and `language_b` must differ for two-way translation",
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.confidence_threshold)
+            || !(0.0..=1.0).contains(&self.confidence_floor)
+        {
+            return Err(SonioxLiveErrors::from(
+                "fields `confidence_threshold` and `confidence_floor` must be between 0 and 1",
+            ));
+        }
+        if self.subtitle_width.is_some_and(|w| w < MIN_SUBTITLE_WIDTH) {
+            return Err(SonioxLiveErrors::from(format!(
+                "field `subtitle_width` must be at least {}",
+                MIN_SUBTITLE_WIDTH
+            )));
+        }
+        if !(0.0..=MAX_OUTLINE_WIDTH).contains(&self.outline_width) {
+            return Err(SonioxLiveErrors::from(format!(
+                "field `outline_width` must be between 0 and {}",
+                MAX_OUTLINE_WIDTH
+            )));
+        }
+        if self.corner_radius < 0.0 || self.line_spacing < 0.0 {
+            return Err(SonioxLiveErrors::from(
+                "fields `corner_radius` and `line_spacing` mustn't be negative",
+            ));
+        }
+        if self.enable_recording && self.export_dir.trim().is_empty() {
+            return Err(SonioxLiveErrors::from(
+                "field `export_dir` mustn't be empty",
+            ));
+        }
+        if self.enable_transcript_log && self.transcript_log_path.trim().is_empty() {
+            return Err(SonioxLiveErrors::from(
+                "field `transcript_log_path` mustn't be empty",
+            ));
+        }
+        for (action, binding) in self.hotkeys.iter() {
+            if let Some(other) = self.hotkeys.conflict(action, binding) {
+                return Err(SonioxLiveErrors::from(format!(
+                    "hotkey `{}` is bound to both `{}` and `{}`",
+                    binding, action, other
+                )));
+            }
+        }
+        self.retry.validate().map_err(SonioxLiveErrors::from)?;
+        Ok(())
+    }
+
+    /// Compares the settings a session was started with against the edited ones.
+    pub fn diff(&self, new: &SettingsApp) -> SettingsChange {
+        let audio = self.backend != new.backend
+            || self.audio_source != new.audio_source
+            || self.audio_host != new.audio_host
+            || self.output_device != new.output_device
+            || self.input_device != new.input_device
+            || self.system_gain != new.system_gain
+            || self.mic_gain != new.mic_gain
+            || self.sample_rate != new.sample_rate
+            || self.channels != new.channels
+            || self.enable_vad != new.enable_vad
+            || self.vad_threshold_db != new.vad_threshold_db
+            || self.vad_hangover_ms != new.vad_hangover_ms
+            || self.reconnect_buffer_secs != new.reconnect_buffer_secs;
+        if audio {
+            return SettingsChange::Restart;
+        }
+        let request = self.endpoint != new.endpoint
+            || self.model != new.model
+            || self.extra_query != new.extra_query
+            || self.extra_headers != new.extra_headers
+            || self.retry != new.retry
+            || self.api_key != new.api_key
+            || self.language_hints != new.language_hints
+            || self.context != new.context
+            || self.enable_speakers != new.enable_speakers
+            || self.enable_language_identification != new.enable_language_identification
+            || self.enable_endpoint_detection != new.enable_endpoint_detection
+            || self.enable_translate != new.enable_translate
+            || (new.enable_translate
+                && (self.translation_mode != new.translation_mode
+                    || self.target_language != new.target_language
+                    || self.language_a != new.language_a
+                    || self.language_b != new.language_b));
+        if request {
+            return SettingsChange::Worker;
+        }
+        if self != new {
+            return SettingsChange::Visual;
+        }
+        SettingsChange::None
+    }
+
     pub fn text_color(&self) -> Color32 {
         Color32::from_rgb(self.text_color.0, self.text_color.1, self.text_color.2)
     }
 
     pub fn get_background_color(&self) -> Color32 {
         if self.enable_background {
-            return Color32::from_black_alpha(155);
+            return rgba(self.background_color);
         }
         Color32::TRANSPARENT
     }
 
+    pub fn corner_radius(&self) -> f32 {
+        self.corner_radius
+    }
+
+    pub fn line_spacing(&self) -> f32 {
+        self.line_spacing
+    }
+
+    pub fn outline(&self) -> Option<Stroke> {
+        (self.enable_outline && self.outline_width > 0.0)
+            .then(|| Stroke::new(self.outline_width, rgba(self.outline_color)))
+    }
+
+    pub fn shadow(&self) -> Option<(Vec2, Color32)> {
+        self.enable_shadow.then(|| {
+            (
+                vec2(self.shadow_offset.0, self.shadow_offset.1),
+                rgba(self.shadow_color),
+            )
+        })
+    }
+
     pub fn get_anchor(&self) -> (Align2, Vec2) {
-        let align = match self.anchor {
-            0 => Align2::LEFT_TOP,
-            1 => Align2::CENTER_TOP,
-            2 => Align2::RIGHT_TOP,
-            3 => Align2::LEFT_CENTER,
-            4 => Align2::CENTER_CENTER,
-            5 => Align2::RIGHT_CENTER,
-            6 => Align2::LEFT_BOTTOM,
-            7 => Align2::CENTER_BOTTOM,
-            8 => Align2::RIGHT_BOTTOM,
-            _ => Align2::CENTER_BOTTOM,
-        };
-        (align, vec2(self.offset.0, self.offset.1))
+        (self.anchor.align(), vec2(self.offset.0, self.offset.1))
     }
 
+    pub fn set_anchor(&mut self, align: Align2, offset: Vec2) {
+        self.anchor = OverlayAnchor::from_align(align);
+        self.offset = (offset.x, offset.y);
+    }
+
+    /// Writes the settings as they are, invalid fields included, so one bad value
+    /// doesn't lose every other edit. [`SettingsApp::validate`] runs when a session starts.
     pub fn save(&self, path: &str) -> Result<(), SonioxLiveErrors> {
         let toml_string = toml::to_string(self)?;
         std::fs::write(path, toml_string)?;
@@ -139,3 +580,7 @@ impl SettingsApp {
         Ok(())
     }
 }
+
+fn rgba(color: (u8, u8, u8, u8)) -> Color32 {
+    Color32::from_rgba_unmultiplied(color.0, color.1, color.2, color.3)
+}
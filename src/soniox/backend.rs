@@ -0,0 +1,137 @@
+use crate::errors::SonioxLiveErrors;
+use crate::settings::SettingsApp;
+use crate::soniox::connection::{SonioxConnection, build_url};
+use crate::soniox::session::{SonioxSessionReader, SonioxSessionWriter};
+use crate::transcription::backend::{BackendSession, TranscriptionBackend};
+use crate::types::backend::{TranscriptToken, TranscriptUpdate, TranslationStatus};
+use crate::types::events::BackendEvent;
+use crate::types::soniox::{
+    SonioxControlMessage, SonioxTranscriptionMessage, SonioxTranscriptionRequest,
+    SonioxTranscriptionResponse, SonioxTranscriptionToken,
+};
+use tungstenite::{Bytes, Message};
+
+const ERROR_CODES_RECONNECT: &[usize] = &[408, 502, 503];
+
+pub struct SonioxBackend {
+    url: String,
+    headers: Vec<(String, String)>,
+    request: SonioxTranscriptionRequest,
+}
+
+impl SonioxBackend {
+    pub fn new(settings: &SettingsApp, request: SonioxTranscriptionRequest) -> Self {
+        Self {
+            url: build_url(settings.endpoint(), settings.extra_query()),
+            headers: settings.extra_headers().to_vec(),
+            request,
+        }
+    }
+}
+
+impl TranscriptionBackend for SonioxBackend {
+    type Session = SonioxSession;
+
+    async fn connect(&self) -> Result<Self::Session, SonioxLiveErrors> {
+        let conn = SonioxConnection::connect(self.url.as_str(), &self.headers).await?;
+        let (writer, reader) = conn.into_session(&self.request).await?;
+        Ok(SonioxSession { writer, reader })
+    }
+}
+
+pub struct SonioxSession {
+    writer: SonioxSessionWriter,
+    reader: SonioxSessionReader,
+}
+
+impl BackendSession for SonioxSession {
+    async fn send_audio(&mut self, samples: &[i16]) -> Result<(), SonioxLiveErrors> {
+        let slice: &[u8] = bytemuck::cast_slice(samples);
+        self.writer.send_bytes(Bytes::copy_from_slice(slice)).await
+    }
+
+    async fn finalize(&mut self) -> Result<(), SonioxLiveErrors> {
+        self.writer
+            .send_control(SonioxControlMessage::Finalize)
+            .await
+    }
+
+    async fn keepalive(&mut self) -> Result<(), SonioxLiveErrors> {
+        self.writer
+            .send_control(SonioxControlMessage::Keepalive)
+            .await
+    }
+
+    /// An empty frame ends the audio, Soniox then sends what's left and closes.
+    async fn close(&mut self) -> Result<(), SonioxLiveErrors> {
+        self.writer.send_bytes(Bytes::new()).await
+    }
+
+    async fn recv_event(&mut self) -> Result<BackendEvent, SonioxLiveErrors> {
+        let message = self.reader.recv_message().await?;
+        let event = match message {
+            Message::Text(txt) => match serde_json::from_str::<SonioxTranscriptionMessage>(&txt) {
+                Ok(parsed_msg) => map_transcription_msg(parsed_msg),
+                Err(e) => {
+                    tracing::warn!("JSON parse error: {}. Raw: {}", e, txt);
+                    BackendEvent::Ignored
+                }
+            },
+            Message::Ping(data) => {
+                let _ = self.writer.send_pong(data).await;
+                BackendEvent::Ignored
+            }
+            Message::Close(_) => {
+                tracing::warn!("Server sent Close frame");
+                BackendEvent::Reconnect
+            }
+            _ => BackendEvent::Ignored,
+        };
+        Ok(event)
+    }
+}
+
+fn map_transcription_msg(msg: SonioxTranscriptionMessage) -> BackendEvent {
+    match msg {
+        SonioxTranscriptionMessage::Response(r) => BackendEvent::Transcription(r.into()),
+        SonioxTranscriptionMessage::Error(e) if ERROR_CODES_RECONNECT.contains(&e.error_code) => {
+            tracing::warn!("Temporary API Error {}: {}", e.error_code, e.error_message);
+            BackendEvent::Reconnect
+        }
+        SonioxTranscriptionMessage::Error(e) => {
+            tracing::error!("Fatal API Error {}: {}", e.error_code, e.error_message);
+            BackendEvent::Fatal(SonioxLiveErrors::API(e.error_code, e.error_message))
+        }
+    }
+}
+
+impl From<SonioxTranscriptionResponse> for TranscriptUpdate {
+    fn from(response: SonioxTranscriptionResponse) -> Self {
+        Self {
+            tokens: response.tokens.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<SonioxTranscriptionToken> for TranscriptToken {
+    fn from(token: SonioxTranscriptionToken) -> Self {
+        let endpoint = token.is_endpoint();
+        let translation = match token.translation_status.as_deref() {
+            Some("original") => TranslationStatus::Original,
+            Some("translation") => TranslationStatus::Translation,
+            _ => TranslationStatus::Untranslated,
+        };
+        Self {
+            text: if endpoint { String::new() } else { token.text },
+            start_ms: token.start_ms,
+            end_ms: token.end_ms,
+            confidence: token.confidence,
+            is_final: token.is_final,
+            endpoint,
+            speaker: token.speaker,
+            language: token.language,
+            source_language: token.source_language,
+            translation,
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::collections::VecDeque;
+
+const REPLAY_CHUNK: usize = 4096;
+
+/// Bounded ring buffer of PCM captured while the worker has no live session.
+/// When it's full the oldest samples are dropped.
+pub struct AudioBacklog {
+    samples: VecDeque<i16>,
+    capacity: usize,
+}
+
+impl AudioBacklog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    pub fn with_duration(seconds: f32, sample_rate: u32, channels: u16) -> Self {
+        let capacity = (seconds.max(0.0) * sample_rate as f32) as usize * channels as usize;
+        Self::new(capacity)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn push(&mut self, data: &[i16]) {
+        if self.capacity == 0 {
+            return;
+        }
+        let data = &data[data.len().saturating_sub(self.capacity)..];
+        let overflow = (self.samples.len() + data.len()).saturating_sub(self.capacity);
+        if overflow > 0 {
+            tracing::debug!("Audio backlog is full, dropping {} samples", overflow);
+            self.samples.drain(..overflow);
+        }
+        self.samples.extend(data);
+    }
+
+    /// Takes the next chunk to replay, oldest samples first.
+    pub fn pop_chunk(&mut self) -> Option<Vec<i16>> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let len = self.samples.len().min(REPLAY_CHUNK);
+        Some(self.samples.drain(..len).collect())
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
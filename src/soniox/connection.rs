@@ -6,14 +6,25 @@ use futures_util::StreamExt;
 use tokio_tungstenite::connect_async;
 use tungstenite::Utf8Bytes;
 use tungstenite::client::IntoClientRequest;
+use tungstenite::http::{HeaderName, HeaderValue};
 
 pub struct SonioxConnection {
     ws_stream: WsStream,
 }
 
 impl SonioxConnection {
-    pub async fn connect(url: impl IntoClientRequest) -> Result<Self, SonioxLiveErrors> {
-        let request = url.into_client_request()?;
+    pub async fn connect(
+        url: impl IntoClientRequest,
+        headers: &[(String, String)],
+    ) -> Result<Self, SonioxLiveErrors> {
+        let mut request = url.into_client_request()?;
+        for (name, value) in headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| SonioxLiveErrors::Internal(format!("invalid header `{}`", name)))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|_| SonioxLiveErrors::Internal(format!("invalid value of `{}`", name)))?;
+            request.headers_mut().insert(name, value);
+        }
         let (ws_stream, _) = connect_async(request).await?;
 
         Ok(Self { ws_stream })
@@ -32,3 +43,32 @@ impl SonioxConnection {
         Ok((writer, reader))
     }
 }
+
+pub(crate) fn build_url(endpoint: &str, query: &[(String, String)]) -> String {
+    let mut url = endpoint.trim().to_string();
+    for (i, (key, value)) in query.iter().enumerate() {
+        let separator = if i == 0 && !url.contains('?') {
+            '?'
+        } else {
+            '&'
+        };
+        url.push(separator);
+        url.push_str(&encode_component(key));
+        url.push('=');
+        url.push_str(&encode_component(value));
+    }
+    url
+}
+
+fn encode_component(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
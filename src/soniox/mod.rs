@@ -2,12 +2,15 @@ use tokio::net::TcpStream;
 use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
 
 pub mod action;
+pub mod backend;
+pub mod backlog;
 pub mod connection;
 pub mod request;
+pub mod retry;
 pub mod session;
 pub mod worker;
 
-pub const URL: &str = "wss://stt-rt.soniox.com/transcribe-websocket";
-pub const MODEL: &str = "stt-rt-v4";
+pub const DEFAULT_URL: &str = "wss://stt-rt.soniox.com/transcribe-websocket";
+pub const DEFAULT_MODEL: &str = "stt-rt-v4";
 
 pub(crate) type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
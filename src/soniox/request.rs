@@ -1,29 +1,41 @@
 use crate::errors::SonioxLiveErrors;
 use crate::settings::SettingsApp;
-use crate::soniox::MODEL;
 use crate::types::soniox::{SonioxTranscriptionRequest, SonioxTranslationObject};
+use crate::types::translation::TranslationMode;
 use cpal::StreamConfig;
 
+/// `stream_config` is the format the audio pipeline uploads, not the device format.
 pub(crate) fn create_request(
     settings: &SettingsApp,
     stream_config: &StreamConfig,
 ) -> Result<SonioxTranscriptionRequest, SonioxLiveErrors> {
     let mut request = SonioxTranscriptionRequest {
         api_key: settings.api_key(),
-        model: MODEL,
+        model: settings.model(),
         audio_format: "pcm_s16le",
         sample_rate: Some(stream_config.sample_rate),
         num_channels: Some(stream_config.channels as u32),
         context: Some(settings.context()),
         language_hints: settings.language_hints(),
         enable_speaker_diarization: Some(settings.enable_speakers()),
+        enable_language_identification: Some(settings.enable_language_identification()),
+        enable_endpoint_detection: Some(settings.enable_endpoint_detection()),
         ..Default::default()
     };
     if settings.enable_translate {
-        request.translation = Some(SonioxTranslationObject {
-            r#type: "one_way",
-            target_language: Some(settings.target_language()),
-            ..Default::default()
+        let mode = settings.translation_mode();
+        request.translation = Some(match mode {
+            TranslationMode::OneWay => SonioxTranslationObject {
+                r#type: mode.as_request_type(),
+                target_language: Some(settings.target_language()),
+                ..Default::default()
+            },
+            TranslationMode::TwoWay => SonioxTranslationObject {
+                r#type: mode.as_request_type(),
+                language_a: Some(settings.language_a()),
+                language_b: Some(settings.language_b()),
+                ..Default::default()
+            },
         });
     }
 
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct RetryPolicy {
+    pub initial_delay_ms: u64,
+    pub multiplier: f32,
+    pub max_delay_ms: u64,
+    /// Fraction of the delay that is randomized in both directions, `0.0..=1.0`.
+    pub jitter: f32,
+    pub max_attempts: u32,
+    pub retry_forever: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay_ms: 1000,
+            multiplier: 2.0,
+            max_delay_ms: 30_000,
+            jitter: 0.2,
+            max_attempts: 5,
+            retry_forever: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn allows(&self, attempt: u32) -> bool {
+        self.retry_forever || attempt <= self.max_attempts
+    }
+
+    /// Delay before the given attempt (starting at 1), without jitter.
+    pub fn base_delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(64) as i32;
+        let delay = self.initial_delay_ms as f64 * (self.multiplier.max(1.0) as f64).powi(exponent);
+        Duration::from_millis(delay.min(self.max_delay_ms as f64) as u64)
+    }
+
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let base = self.base_delay(attempt).as_millis() as f64;
+        let jitter = self.jitter.clamp(0.0, 1.0) as f64;
+        let factor = 1.0 + jitter * (random_unit() * 2.0 - 1.0);
+        let delay = (base * factor).min(self.max_delay_ms as f64);
+        Duration::from_millis(delay as u64)
+    }
+
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.multiplier < 1.0 {
+            return Err("retry multiplier must be at least 1.0");
+        }
+        if !(0.0..=1.0).contains(&self.jitter) {
+            return Err("retry jitter must be between 0.0 and 1.0");
+        }
+        if self.initial_delay_ms > self.max_delay_ms {
+            return Err("initial retry delay mustn't exceed the maximum delay");
+        }
+        Ok(())
+    }
+}
+
+fn random_unit() -> f64 {
+    let value = RandomState::new().build_hasher().finish();
+    (value >> 11) as f64 / (1u64 << 53) as f64
+}
@@ -1,5 +1,6 @@
 use crate::errors::SonioxLiveErrors;
 use crate::soniox::WsStream;
+use crate::types::soniox::SonioxControlMessage;
 use futures_util::stream::{SplitSink, SplitStream};
 use futures_util::{SinkExt, StreamExt};
 use tungstenite::{Bytes, Message, Utf8Bytes};
@@ -30,6 +31,14 @@ impl SonioxSessionWriter {
         Ok(())
     }
 
+    pub async fn send_control(
+        &mut self,
+        message: SonioxControlMessage,
+    ) -> Result<(), SonioxLiveErrors> {
+        tracing::debug!("Sending {:?}", message);
+        self.send_text(serde_json::to_string(&message)?).await
+    }
+
     pub async fn send_bytes(&mut self, data: impl Into<Bytes>) -> Result<(), SonioxLiveErrors> {
         let message = Message::Binary(data.into());
         self.0.send(message).await?;
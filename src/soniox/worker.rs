@@ -1,121 +1,202 @@
 use crate::errors::SonioxLiveErrors;
-use crate::soniox::URL;
 use crate::soniox::action::StreamAction;
-use crate::soniox::connection::SonioxConnection;
-use crate::soniox::session::{SonioxSessionReader, SonioxSessionWriter};
+use crate::soniox::backlog::AudioBacklog;
+use crate::soniox::retry::RetryPolicy;
+use crate::transcription::backend::{BackendSession, TranscriptionBackend};
+use crate::transcription::clock::AudioClock;
 use crate::types::audio::AudioSample;
-use crate::types::events::SonioxEvent;
-use crate::types::soniox::{SonioxTranscriptionMessage, SonioxTranscriptionRequest};
+use crate::types::events::{BackendEvent, SonioxEvent, WorkerCommand};
+use std::future::{Future, pending};
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc::{Receiver, Sender};
-use tokio::time::sleep;
-use tungstenite::{Bytes, Message};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, sleep, sleep_until};
 
-const MAX_RETRIES: u32 = 5;
-const RECONNECT_DELAY: u64 = 1000;
-const ERROR_CODES_RECONNECT: &[usize] = &[408, 502, 503];
+/// Soniox closes sessions that get no audio for 20 seconds.
+pub const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(10);
+/// A session that stayed up this long resets the retry attempts.
+pub const STABLE_SESSION: Duration = Duration::from_secs(30);
+/// How long a stopped session may take to deliver the rest of its results.
+pub const STOP_GRACE: Duration = Duration::from_secs(3);
 
-pub(crate) struct SonioxWorker {
+pub struct SonioxWorker {
     rx_audio: Receiver<AudioSample>,
+    rx_command: Receiver<WorkerCommand>,
     tx_recycle: Sender<AudioSample>,
     tx_event: Sender<SonioxEvent>,
+    retry_policy: RetryPolicy,
+    backlog: AudioBacklog,
+    keepalive_interval: Duration,
+    clock: Option<Arc<AudioClock>>,
+    /// Samples taken from the audio channel so far.
+    received: u64,
+    /// Position of the first sample the current session got, see [`AudioClock`].
+    session_start: u64,
+    audio_closed: bool,
+    commands_closed: bool,
+    /// Stop signal of the current [`Self::serve`] run.
+    stop: Option<oneshot::Receiver<()>>,
+    stopping: bool,
 }
 
 impl SonioxWorker {
-    pub(crate) fn new(
+    pub fn new(
         rx_audio: Receiver<AudioSample>,
+        rx_command: Receiver<WorkerCommand>,
         tx_recycle: Sender<AudioSample>,
         tx_event: Sender<SonioxEvent>,
+        retry_policy: RetryPolicy,
+        backlog: AudioBacklog,
     ) -> Self {
         Self {
             rx_audio,
+            rx_command,
             tx_event,
             tx_recycle,
+            retry_policy,
+            backlog,
+            keepalive_interval: KEEPALIVE_INTERVAL,
+            clock: None,
+            received: 0,
+            session_start: 0,
+            audio_closed: false,
+            commands_closed: false,
+            stop: None,
+            stopping: false,
         }
     }
 
-    pub(crate) async fn run(
+    pub fn with_keepalive_interval(mut self, interval: Duration) -> Self {
+        self.keepalive_interval = interval;
+        self
+    }
+
+    /// Moves token timestamps onto the capture timeline of `clock`, so gaps in the
+    /// uploaded audio and replayed audio keep their real time.
+    pub fn with_clock(mut self, clock: Arc<AudioClock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = retry_policy;
+    }
+
+    pub async fn run<B: TranscriptionBackend>(
         mut self,
-        request: &SonioxTranscriptionRequest,
+        backend: &B,
+    ) -> Result<(), SonioxLiveErrors> {
+        let (_stop, stop) = oneshot::channel();
+        self.serve(backend, stop).await
+    }
+
+    /// Like [`Self::run`], but returns once `stop` fires and keeps the worker with its
+    /// channels and backlog, so it can be served again, e.g. with a request built from
+    /// new settings. A dropped sender never stops the run.
+    pub async fn serve<B: TranscriptionBackend>(
+        &mut self,
+        backend: &B,
+        stop: oneshot::Receiver<()>,
     ) -> Result<(), SonioxLiveErrors> {
         let mut retry_count = 0;
         let mut flag_first_connection = true;
+        // Finalize requests still queued were meant for the previous session.
+        while self.rx_command.try_recv().is_ok() {}
+        self.stop = Some(stop);
+        self.stopping = false;
 
         loop {
-            let first_packet = if retry_count == 0 {
+            if self.stopping {
+                tracing::info!("Worker stopped by command");
+                return Ok(());
+            }
+            if retry_count == 0 && self.backlog.is_empty() {
                 tracing::debug!("Waiting for audio input to connect...");
-                match self.rx_audio.recv().await {
-                    Some(packet) => packet,
-                    None => {
-                        tracing::info!("Audio channel closed. Exiting worker.");
-                        return Ok(());
+                tokio::select! {
+                    audio_opt = self.rx_audio.recv() => match audio_opt {
+                        Some(packet) => self.buffer_audio(packet),
+                        None => {
+                            tracing::info!("Audio channel closed. Exiting worker.");
+                            return Ok(());
+                        }
+                    },
+                    command = self.rx_command.recv(), if !self.commands_closed => {
+                        self.handle_idle_command(command);
+                        continue;
+                    }
+                    _ = stop_requested(&mut self.stop) => {
+                        self.stopping = true;
+                        continue;
                     }
                 }
-            } else {
-                Vec::new()
-            };
+            }
 
-            tracing::debug!("Connecting to Soniox... (Attempt {})", retry_count + 1);
-            let conn_result = SonioxConnection::connect(URL).await;
-            let conn = match conn_result {
-                Ok(c) => c,
+            tracing::debug!("Connecting to backend... (Attempt {})", retry_count + 1);
+            let mut session = match self.connect_buffering(backend).await {
+                Ok(s) => s,
+                Err(_) if self.stopping => continue,
                 Err(e) => {
                     tracing::warn!("Connection failed: {}", e);
                     if self.handle_reconnect(&mut retry_count).await.is_err() {
                         return Err(SonioxLiveErrors::ConnectionLost);
                     }
+                    if self.audio_closed {
+                        tracing::info!("Audio channel closed during reconnect. Exiting worker.");
+                        return Ok(());
+                    }
                     continue;
                 }
             };
-            let session_result = conn.into_session(request).await;
-            let (mut writer, reader) = match session_result {
-                Ok((w, r)) => (w, r),
+
+            tracing::info!("Connected to backend");
+            let connected_at = Instant::now();
+            // The session starts with the backlog, captured before it connected.
+            self.session_start = self.received - self.backlog.len() as u64;
+            let action = match self.replay_backlog(&mut session).await {
+                Ok(()) => {
+                    let _ = self
+                        .tx_event
+                        .send(SonioxEvent::Connected(flag_first_connection))
+                        .await;
+                    flag_first_connection = false;
+                    self.run_session_loop(session).await
+                }
                 Err(e) => {
-                    tracing::warn!("Handshake failed: {}", e);
-                    if self.handle_reconnect(&mut retry_count).await.is_err() {
-                        return Err(SonioxLiveErrors::ConnectionLost);
-                    }
-                    continue;
+                    tracing::error!("Failed to replay buffered audio: {}", e);
+                    StreamAction::Reconnect
                 }
             };
-
-            tracing::info!("Connected to Soniox");
-            retry_count = 0;
-            if !first_packet.is_empty()
-                && let Err(e) = self.handle_audio(first_packet, &mut writer).await
-            {
-                tracing::error!("Failed to send initial audio: {}", e);
-                continue;
-            }
-
-            let _ = self
-                .tx_event
-                .send(SonioxEvent::Connected(flag_first_connection))
-                .await;
-            if flag_first_connection {
-                flag_first_connection = false;
-            }
-
-            let action = self.run_session_loop(writer, reader).await;
             match action {
                 StreamAction::Stop => {
                     tracing::info!("Worker stopped normally");
                     return Ok(());
                 }
                 StreamAction::Reconnect => {
-                    tracing::warn!("Session ended. Going to standby...");
+                    tracing::warn!("Session ended. Reconnecting...");
+                    // Only a session that held up counts as recovered, a server that
+                    // keeps failing right after the handshake still backs off.
+                    if connected_at.elapsed() >= STABLE_SESSION {
+                        retry_count = 0;
+                    }
+                    if self.handle_reconnect(&mut retry_count).await.is_err() {
+                        return Err(SonioxLiveErrors::ConnectionLost);
+                    }
+                    if self.audio_closed {
+                        tracing::info!("Audio channel closed during reconnect. Exiting worker.");
+                        return Ok(());
+                    }
                 }
                 StreamAction::Continue => {}
             }
         }
     }
 
-    async fn run_session_loop(
-        &mut self,
-        mut writer: SonioxSessionWriter,
-        mut reader: SonioxSessionReader,
-    ) -> StreamAction {
+    async fn run_session_loop<S: BackendSession>(&mut self, mut session: S) -> StreamAction {
+        let mut last_sent = Instant::now();
         loop {
+            let keepalive_at = last_sent + self.keepalive_interval;
             tokio::select! {
                 audio_opt = self.rx_audio.recv() => {
                     let Some(buffer) = audio_opt else {
@@ -123,14 +204,38 @@ impl SonioxWorker {
                         return StreamAction::Stop;
                     };
 
-                    if let Err(e) = self.handle_audio(buffer, &mut writer).await {
+                    if let Err(e) = self.handle_audio(buffer, &mut session).await {
                         tracing::error!("Failed to send audio: {}", e);
                         return StreamAction::Reconnect;
                     }
+                    last_sent = Instant::now();
+                }
+                command = self.rx_command.recv(), if !self.commands_closed => {
+                    if command.is_none() {
+                        self.commands_closed = true;
+                        continue;
+                    }
+                    if let Err(e) = session.finalize().await {
+                        tracing::error!("Failed to send finalize: {}", e);
+                        return StreamAction::Reconnect;
+                    }
+                    last_sent = Instant::now();
+                }
+                _ = stop_requested(&mut self.stop) => {
+                    self.stopping = true;
+                    self.finish_session(&mut session).await;
+                    return StreamAction::Stop;
+                }
+                _ = sleep_until(keepalive_at) => {
+                    if let Err(e) = session.keepalive().await {
+                        tracing::error!("Failed to send keepalive: {}", e);
+                        return StreamAction::Reconnect;
+                    }
+                    last_sent = Instant::now();
                 }
-                msg_result = reader.recv_message() => {
-                    let action = match msg_result {
-                        Ok(m) => self.handle_ws_message(m, &mut writer).await,
+                event_result = session.recv_event() => {
+                    let action = match event_result {
+                        Ok(event) => self.handle_backend_event(event).await,
                         Err(e) => {
                             tracing::error!("WS Error/EOF: {}", e);
                             let _ = self.tx_event.send(SonioxEvent::from("Connection interrupted")).await;
@@ -145,50 +250,144 @@ impl SonioxWorker {
         }
     }
 
-    async fn handle_audio(
-        &self,
+    /// Finalizes the interim text and ends the audio, then forwards the results the
+    /// session still sends until it closes. Audio captured meanwhile goes to the backlog
+    /// for the next session.
+    async fn finish_session<S: BackendSession>(&mut self, session: &mut S) {
+        if let Err(e) = session.finalize().await {
+            tracing::warn!("Failed to finalize stopped session: {}", e);
+            return;
+        }
+        if let Err(e) = session.close().await {
+            tracing::warn!("Failed to close stopped session: {}", e);
+            return;
+        }
+        let deadline = sleep(STOP_GRACE);
+        tokio::pin!(deadline);
+        loop {
+            tokio::select! {
+                _ = &mut deadline => {
+                    tracing::warn!("Stopped session didn't close in time, pending text may be lost");
+                    return;
+                }
+                audio_opt = self.rx_audio.recv(), if !self.audio_closed => match audio_opt {
+                    Some(buffer) => self.buffer_audio(buffer),
+                    None => self.audio_closed = true,
+                },
+                event_result = session.recv_event() => {
+                    let Ok(event) = event_result else {
+                        return;
+                    };
+                    if !matches!(self.handle_backend_event(event).await, StreamAction::Continue) {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    async fn handle_audio<S: BackendSession>(
+        &mut self,
         mut buffer: AudioSample,
-        writer: &mut SonioxSessionWriter,
+        session: &mut S,
     ) -> Result<(), SonioxLiveErrors> {
         if buffer.is_empty() {
             return Ok(());
         }
 
-        let slice: &[u8] = bytemuck::cast_slice(&buffer);
-        writer.send_bytes(Bytes::copy_from_slice(slice)).await?;
+        if let Err(e) = session.send_audio(&buffer).await {
+            self.buffer_audio(buffer);
+            return Err(e);
+        }
+        self.received += buffer.len() as u64;
         buffer.clear();
         let _ = self.tx_recycle.send(buffer).await;
         Ok(())
     }
 
-    async fn handle_ws_message(
-        &self,
-        message: Message,
-        writer: &mut SonioxSessionWriter,
-    ) -> StreamAction {
-        match message {
-            Message::Text(txt) => match serde_json::from_str::<SonioxTranscriptionMessage>(&txt) {
-                Ok(parsed_msg) => self.process_transcription_msg(parsed_msg).await,
-                Err(e) => {
-                    tracing::warn!("JSON parse error: {}. Raw: {}", e, txt);
-                    StreamAction::Continue
+    fn buffer_audio(&mut self, mut buffer: AudioSample) {
+        self.received += buffer.len() as u64;
+        self.backlog.push(&buffer);
+        buffer.clear();
+        let _ = self.tx_recycle.try_send(buffer);
+    }
+
+    async fn replay_backlog<S: BackendSession>(
+        &mut self,
+        session: &mut S,
+    ) -> Result<(), SonioxLiveErrors> {
+        if !self.backlog.is_empty() {
+            tracing::debug!("Replaying {} buffered samples", self.backlog.len());
+        }
+        while let Some(chunk) = self.backlog.pop_chunk() {
+            if let Err(e) = session.send_audio(&chunk).await {
+                self.backlog.clear();
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Connects to the backend while draining captured audio into the backlog,
+    /// so the capture channel doesn't overflow during a slow handshake.
+    async fn connect_buffering<B: TranscriptionBackend>(
+        &mut self,
+        backend: &B,
+    ) -> Result<B::Session, SonioxLiveErrors> {
+        let connect = backend.connect();
+        tokio::pin!(connect);
+        loop {
+            tokio::select! {
+                result = &mut connect => return result,
+                audio_opt = self.rx_audio.recv(), if !self.audio_closed => match audio_opt {
+                    Some(buffer) => self.buffer_audio(buffer),
+                    None => self.audio_closed = true,
+                },
+                command = self.rx_command.recv(), if !self.commands_closed => {
+                    self.handle_idle_command(command);
+                }
+                _ = stop_requested(&mut self.stop) => {
+                    self.stopping = true;
+                    return Err(SonioxLiveErrors::from("worker stopped while connecting"));
                 }
-            },
-            Message::Ping(data) => {
-                let _ = writer.send_pong(data).await;
-                StreamAction::Continue
             }
-            Message::Close(_) => {
-                tracing::warn!("Server sent Close frame");
-                StreamAction::Reconnect
+        }
+    }
+
+    async fn sleep_buffering(&mut self, delay: Duration) {
+        let timer = sleep(delay);
+        tokio::pin!(timer);
+        loop {
+            tokio::select! {
+                _ = &mut timer => return,
+                audio_opt = self.rx_audio.recv(), if !self.audio_closed => match audio_opt {
+                    Some(buffer) => self.buffer_audio(buffer),
+                    None => self.audio_closed = true,
+                },
+                command = self.rx_command.recv(), if !self.commands_closed => {
+                    self.handle_idle_command(command);
+                }
+                _ = stop_requested(&mut self.stop) => {
+                    self.stopping = true;
+                    return;
+                }
             }
-            _ => StreamAction::Continue,
         }
     }
 
-    async fn process_transcription_msg(&self, msg: SonioxTranscriptionMessage) -> StreamAction {
-        match msg {
-            SonioxTranscriptionMessage::Response(r) => {
+    /// Without a session there is nothing to finalize.
+    fn handle_idle_command(&mut self, command: Option<WorkerCommand>) {
+        if command.is_none() {
+            self.commands_closed = true;
+        }
+    }
+
+    async fn handle_backend_event(&self, event: BackendEvent) -> StreamAction {
+        match event {
+            BackendEvent::Transcription(mut r) => {
+                if let Some(clock) = &self.clock {
+                    clock.stamp(&mut r, self.session_start);
+                }
                 if self
                     .tx_event
                     .send(SonioxEvent::Transcription(r))
@@ -199,37 +398,135 @@ impl SonioxWorker {
                 }
                 StreamAction::Continue
             }
-            SonioxTranscriptionMessage::Error(e)
-                if ERROR_CODES_RECONNECT.contains(&e.error_code) =>
-            {
-                tracing::warn!("Temporary API Error {}: {}", e.error_code, e.error_message);
-                StreamAction::Reconnect
-            }
-            SonioxTranscriptionMessage::Error(e) => {
-                tracing::error!("Fatal API Error {}: {}", e.error_code, e.error_message);
-                let _ = self
-                    .tx_event
-                    .send(SonioxEvent::Error(SonioxLiveErrors::API(
-                        e.error_code,
-                        e.error_message,
-                    )))
-                    .await;
+            BackendEvent::Reconnect => StreamAction::Reconnect,
+            BackendEvent::Fatal(e) => {
+                let _ = self.tx_event.send(SonioxEvent::Error(e)).await;
                 StreamAction::Stop
             }
+            BackendEvent::Ignored => StreamAction::Continue,
         }
     }
 
-    async fn handle_reconnect(&self, retry_count: &mut u32) -> Result<(), ()> {
-        sleep(Duration::from_millis(RECONNECT_DELAY)).await;
+    async fn handle_reconnect(&mut self, retry_count: &mut u32) -> Result<(), ()> {
         *retry_count += 1;
-
-        if *retry_count > MAX_RETRIES {
+        if !self.retry_policy.allows(*retry_count) {
             let _ = self
                 .tx_event
                 .send(SonioxEvent::Error(SonioxLiveErrors::ConnectionLost))
                 .await;
             return Err(());
         }
+
+        let delay = self.retry_policy.delay(*retry_count);
+        tracing::debug!("Reconnecting in {:?} (attempt {})", delay, retry_count);
+        let _ = self
+            .tx_event
+            .send(SonioxEvent::Reconnecting {
+                attempt: *retry_count,
+                delay,
+            })
+            .await;
+        self.sleep_buffering(delay).await;
         Ok(())
     }
 }
+
+/// Resolves once the current run should stop. Never resolves if the sender is gone.
+async fn stop_requested(stop: &mut Option<oneshot::Receiver<()>>) {
+    if let Some(rx) = stop.as_mut() {
+        let signalled = rx.await.is_ok();
+        *stop = None;
+        if signalled {
+            return;
+        }
+    }
+    pending().await
+}
+
+/// A [`SonioxWorker`] served on its own task. Restarting hands the worker, with its
+/// channels and backlog, over to the next backend once the current run has stopped.
+pub struct WorkerTask {
+    handle: Option<JoinHandle<Option<SonioxWorker>>>,
+    stop: Option<oneshot::Sender<()>>,
+    tx_event: Sender<SonioxEvent>,
+}
+
+impl WorkerTask {
+    pub fn spawn<B: TranscriptionBackend>(
+        worker: SonioxWorker,
+        backend: B,
+        tx_event: Sender<SonioxEvent>,
+    ) -> Self {
+        let mut task = Self {
+            handle: None,
+            stop: None,
+            tx_event,
+        };
+        task.serve(async { Some(worker) }, backend);
+        task
+    }
+
+    /// Stops the current run and serves the worker with `backend` after it. Restarts
+    /// issued in a row each stop their own run, so the last backend always wins.
+    pub fn restart<B: TranscriptionBackend>(
+        &mut self,
+        backend: B,
+        retry_policy: RetryPolicy,
+    ) -> Result<(), SonioxLiveErrors> {
+        let Some(previous) = self.handle.take() else {
+            return Err(SonioxLiveErrors::from("transcription worker is gone"));
+        };
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+
+        let tx_event = self.tx_event.clone();
+        let worker = async move {
+            match previous.await {
+                Ok(Some(mut worker)) => {
+                    worker.set_retry_policy(retry_policy);
+                    Some(worker)
+                }
+                // The run that lost the worker already reported it.
+                Ok(None) => None,
+                Err(e) => {
+                    tracing::error!("Transcription worker failed: {}", e);
+                    let _ = tx_event
+                        .send(SonioxEvent::Error(SonioxLiveErrors::from(
+                            "transcription worker failed, restart the session",
+                        )))
+                        .await;
+                    None
+                }
+            }
+        };
+        self.serve(worker, backend);
+        Ok(())
+    }
+
+    fn serve<B: TranscriptionBackend>(
+        &mut self,
+        worker: impl Future<Output = Option<SonioxWorker>> + Send + 'static,
+        backend: B,
+    ) {
+        let (stop, rx_stop) = oneshot::channel();
+        let tx_event = self.tx_event.clone();
+        self.stop = Some(stop);
+        self.handle = Some(tokio::spawn(async move {
+            let mut worker = worker.await?;
+            if let Err(e) = worker.serve(&backend, rx_stop).await {
+                tracing::error!("Backend error: {:?}", e);
+                let _ = tx_event.send(SonioxEvent::Error(e)).await;
+            }
+            Some(worker)
+        }));
+    }
+}
+
+impl Drop for WorkerTask {
+    fn drop(&mut self) {
+        if let Some(handle) = &self.handle {
+            handle.abort();
+        }
+    }
+}
@@ -1,67 +1,307 @@
 use crate::errors::SonioxLiveErrors;
-use crate::transcription::utils::convert_audio_chunk;
-use crate::types::audio::AudioSample;
-use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{Stream, StreamConfig};
+use crate::settings::SettingsApp;
+use crate::transcription::clock::AudioClock;
+use crate::transcription::devices::{DeviceKind, select_device};
+use crate::transcription::mixer::AudioMixer;
+use crate::transcription::utils::{AudioConverter, ToPcm16, convert_audio_chunk};
+use crate::transcription::vad::{PreRoll, VoiceGate};
+use crate::types::audio::{AudioSample, AudioSource};
+use crate::types::events::SonioxEvent;
+use cpal::traits::{DeviceTrait, StreamTrait};
+use cpal::{Device, SampleFormat, Stream, StreamConfig, SupportedStreamConfig};
+use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc::error::TrySendError;
 use tokio::sync::mpsc::{Receiver, Sender};
 
+const MIXER_LATENCY_MS: u32 = 200;
+
 pub struct AudioSession {
-    stream: Stream,
+    streams: Vec<Stream>,
     config: StreamConfig,
+    clock: Arc<AudioClock>,
+}
+
+/// Tail of the capture pipeline: voice gate and hand-off to the worker.
+struct AudioSink {
+    tx_audio: Sender<AudioSample>,
+    rx_recycle: Receiver<AudioSample>,
+    tx_event: Sender<SonioxEvent>,
+    gate: Option<VoiceGate>,
+    pre_roll: PreRoll,
+    spare: Option<AudioSample>,
+    clock: Arc<AudioClock>,
+}
+
+/// State shared by both capture callbacks in mixed mode.
+struct MixerState {
+    mixer: AudioMixer,
+    sink: AudioSink,
 }
 
 impl AudioSession {
-    pub fn new(config: StreamConfig, stream: Stream) -> Self {
-        Self { config, stream }
+    pub fn new(config: StreamConfig, streams: Vec<Stream>, clock: Arc<AudioClock>) -> Self {
+        Self {
+            config,
+            streams,
+            clock,
+        }
     }
 
     pub fn open(
+        settings: &SettingsApp,
         tx_audio: Sender<AudioSample>,
-        mut rx_recycle: Receiver<AudioSample>,
+        rx_recycle: Receiver<AudioSample>,
+        tx_event: Sender<SonioxEvent>,
     ) -> Result<Self, SonioxLiveErrors> {
-        let host = cpal::default_host();
-        let device = host
-            .default_output_device()
-            .ok_or_else(|| SonioxLiveErrors::NotFoundOutputDevice)?;
-
-        let config = device.default_output_config()?.config();
-        let stream = device.build_input_stream(
-            &config,
-            move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                let mut buffer = match rx_recycle.try_recv() {
-                    Ok(sample) => sample,
-                    Err(_) => Vec::with_capacity(data.len()),
-                };
-                convert_audio_chunk(data, &mut buffer);
-                match tx_audio.try_send(buffer) {
-                    Ok(_) => {}
-                    Err(TrySendError::Full(_)) => {
-                        tracing::debug!("Audio buffer is full");
-                    }
-                    Err(TrySendError::Closed(_)) => {
-                        tracing::debug!("Capture channel closed");
-                    }
-                }
-            },
-            |err| {
-                tracing::error!("Error in audio callback: {}", err);
-            },
-            None,
-        )?;
-
-        Ok(Self::new(config, stream))
+        let config = wire_config(settings);
+        let clock = Arc::new(AudioClock::new(config.sample_rate, config.channels));
+        let sink = AudioSink::new(settings, &config, tx_audio, rx_recycle, tx_event, clock);
+        match settings.audio_source() {
+            AudioSource::SystemOutput => Self::open_single(
+                settings,
+                DeviceKind::Output,
+                settings.output_device(),
+                config,
+                sink,
+            ),
+            AudioSource::Microphone => Self::open_single(
+                settings,
+                DeviceKind::Input,
+                settings.input_device(),
+                config,
+                sink,
+            ),
+            AudioSource::Mixed => Self::open_mixed(settings, config, sink),
+        }
+    }
+
+    fn open_single(
+        settings: &SettingsApp,
+        kind: DeviceKind,
+        name: Option<&str>,
+        config: StreamConfig,
+        mut sink: AudioSink,
+    ) -> Result<Self, SonioxLiveErrors> {
+        let (device, device_config) = select_device(settings.audio_host(), kind, name)?;
+        let clock = sink.clock.clone();
+        let mut converter = AudioConverter::new(
+            device_config.sample_rate(),
+            device_config.channels(),
+            config.sample_rate,
+            config.channels,
+        );
+        let stream = build_capture_stream(&device, &device_config, move |samples| {
+            let mut buffer = sink.buffer(samples.len());
+            converter.process(samples, &mut buffer);
+            sink.send(buffer);
+        })?;
+
+        Ok(Self::new(config, vec![stream], clock))
+    }
+
+    /// Opens the loopback and the microphone streams and mixes them into one stream.
+    fn open_mixed(
+        settings: &SettingsApp,
+        config: StreamConfig,
+        sink: AudioSink,
+    ) -> Result<Self, SonioxLiveErrors> {
+        let host = settings.audio_host();
+        let (output, output_config) =
+            select_device(host, DeviceKind::Output, settings.output_device())?;
+        let (input, input_config) =
+            select_device(host, DeviceKind::Input, settings.input_device())?;
+
+        let clock = sink.clock.clone();
+        let max_latency =
+            (config.sample_rate * MIXER_LATENCY_MS / 1000) as usize * config.channels as usize;
+        let state = Arc::new(Mutex::new(MixerState {
+            mixer: AudioMixer::new([settings.system_gain(), settings.mic_gain()], max_latency),
+            sink,
+        }));
+        let streams = vec![
+            build_mixed_stream(&output, &output_config, 0, &config, state.clone())?,
+            build_mixed_stream(&input, &input_config, 1, &config, state)?,
+        ];
+
+        Ok(Self::new(config, streams, clock))
     }
 
     pub fn config(&self) -> &StreamConfig {
         &self.config
     }
 
+    pub fn clock(&self) -> &Arc<AudioClock> {
+        &self.clock
+    }
+
     pub fn play(&self) -> Result<(), cpal::PlayStreamError> {
-        self.stream.play()
+        self.streams.iter().try_for_each(|s| s.play())
     }
 
     pub fn pause(&self) -> Result<(), cpal::PauseStreamError> {
-        self.stream.pause()
+        self.streams.iter().try_for_each(|s| s.pause())
     }
 }
+
+impl AudioSink {
+    fn new(
+        settings: &SettingsApp,
+        config: &StreamConfig,
+        tx_audio: Sender<AudioSample>,
+        rx_recycle: Receiver<AudioSample>,
+        tx_event: Sender<SonioxEvent>,
+        clock: Arc<AudioClock>,
+    ) -> Self {
+        let gate = settings.enable_vad().then(|| {
+            VoiceGate::new(
+                settings.vad_threshold_db(),
+                settings.vad_hangover_ms(),
+                config.sample_rate,
+                config.channels,
+            )
+        });
+        if gate.is_some() {
+            // The gate starts closed, the overlay shows that until speech opens it.
+            let _ = tx_event.try_send(SonioxEvent::VoiceActivity(false));
+        }
+        Self {
+            tx_audio,
+            rx_recycle,
+            tx_event,
+            pre_roll: PreRoll::new(gate.as_ref().map_or(0, VoiceGate::hangover)),
+            gate,
+            spare: None,
+            clock,
+        }
+    }
+
+    fn buffer(&mut self, capacity: usize) -> AudioSample {
+        if let Some(buffer) = self.spare.take() {
+            return buffer;
+        }
+        match self.rx_recycle.try_recv() {
+            Ok(sample) => sample,
+            Err(_) => Vec::with_capacity(capacity),
+        }
+    }
+
+    fn send(&mut self, buffer: AudioSample) {
+        let captured = self.clock.capture(buffer.len());
+        if let Some(gate) = &mut self.gate {
+            let was_active = gate.is_active();
+            let admitted = gate.process(&buffer);
+            if admitted != was_active {
+                let _ = self.tx_event.try_send(SonioxEvent::VoiceActivity(admitted));
+            }
+            if !admitted {
+                self.spare = self.pre_roll.push(buffer);
+                return;
+            }
+            if !was_active {
+                // The kept chunks were captured right before this one.
+                let mut position = captured - self.pre_roll.len() as u64;
+                let pre_roll: Vec<_> = self.pre_roll.drain().collect();
+                for chunk in pre_roll {
+                    let len = chunk.len();
+                    self.upload(chunk, position);
+                    position += len as u64;
+                }
+            }
+        }
+        self.upload(buffer, captured);
+    }
+
+    fn upload(&mut self, buffer: AudioSample, captured: u64) {
+        let len = buffer.len();
+        match self.tx_audio.try_send(buffer) {
+            Ok(_) => self.clock.upload(captured, len),
+            Err(TrySendError::Full(_)) => {
+                tracing::debug!("Audio buffer is full");
+            }
+            Err(TrySendError::Closed(_)) => {
+                tracing::debug!("Capture channel closed");
+            }
+        }
+    }
+}
+
+/// Format of the PCM handed to the worker, whatever the device delivers.
+fn wire_config(settings: &SettingsApp) -> StreamConfig {
+    StreamConfig {
+        channels: settings.channels(),
+        sample_rate: settings.sample_rate(),
+        buffer_size: cpal::BufferSize::Default,
+    }
+}
+
+fn build_mixed_stream(
+    device: &Device,
+    device_config: &SupportedStreamConfig,
+    source: usize,
+    target: &StreamConfig,
+    state: Arc<Mutex<MixerState>>,
+) -> Result<Stream, SonioxLiveErrors> {
+    let mut converter = AudioConverter::new(
+        device_config.sample_rate(),
+        device_config.channels(),
+        target.sample_rate,
+        target.channels,
+    );
+    let mut converted = Vec::new();
+    build_capture_stream(device, device_config, move |samples| {
+        converter.process(samples, &mut converted);
+
+        let Ok(mut state) = state.lock() else {
+            return;
+        };
+        state.mixer.push(source, &converted);
+        let mut buffer = state.sink.buffer(converted.len());
+        state.mixer.drain(&mut buffer);
+        if buffer.is_empty() {
+            state.sink.spare = Some(buffer);
+        } else {
+            state.sink.send(buffer);
+        }
+    })
+}
+
+/// Builds an input stream in the device's native sample format and hands every
+/// callback to `on_data` as interleaved 16-bit PCM.
+fn build_capture_stream(
+    device: &Device,
+    device_config: &SupportedStreamConfig,
+    on_data: impl FnMut(&[i16]) + Send + 'static,
+) -> Result<Stream, SonioxLiveErrors> {
+    let config = device_config.config();
+    match device_config.sample_format() {
+        SampleFormat::F32 => build_typed_stream::<f32>(device, &config, on_data),
+        SampleFormat::F64 => build_typed_stream::<f64>(device, &config, on_data),
+        SampleFormat::I8 => build_typed_stream::<i8>(device, &config, on_data),
+        SampleFormat::U8 => build_typed_stream::<u8>(device, &config, on_data),
+        SampleFormat::I16 => build_typed_stream::<i16>(device, &config, on_data),
+        SampleFormat::U16 => build_typed_stream::<u16>(device, &config, on_data),
+        SampleFormat::I32 => build_typed_stream::<i32>(device, &config, on_data),
+        SampleFormat::U32 => build_typed_stream::<u32>(device, &config, on_data),
+        format => Err(SonioxLiveErrors::AudioFormat(format)),
+    }
+}
+
+fn build_typed_stream<T: ToPcm16>(
+    device: &Device,
+    config: &StreamConfig,
+    mut on_data: impl FnMut(&[i16]) + Send + 'static,
+) -> Result<Stream, SonioxLiveErrors> {
+    let mut samples = Vec::new();
+    let stream = device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            convert_audio_chunk(data, &mut samples);
+            on_data(&samples);
+        },
+        |err| {
+            tracing::error!("Error in audio callback: {}", err);
+        },
+        None,
+    )?;
+    Ok(stream)
+}
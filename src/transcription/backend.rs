@@ -0,0 +1,43 @@
+use crate::errors::SonioxLiveErrors;
+use crate::types::events::BackendEvent;
+use std::future::Future;
+
+/// A speech recognition service the worker can stream audio into.
+///
+/// Implementations own everything service-specific (endpoint, handshake, wire format),
+/// the worker only drives reconnects and forwards normalized events to the app.
+pub trait TranscriptionBackend: Send + Sync + 'static {
+    type Session: BackendSession;
+
+    fn connect(&self) -> impl Future<Output = Result<Self::Session, SonioxLiveErrors>> + Send;
+}
+
+/// A single live connection opened by [`TranscriptionBackend::connect`].
+pub trait BackendSession: Send {
+    fn send_audio(
+        &mut self,
+        samples: &[i16],
+    ) -> impl Future<Output = Result<(), SonioxLiveErrors>> + Send;
+
+    /// Forces pending interim results to become final. Services without such a
+    /// control just ignore it.
+    fn finalize(&mut self) -> impl Future<Output = Result<(), SonioxLiveErrors>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Called while no audio is flowing, e.g. when the voice gate is closed or
+    /// capture is paused, so the service doesn't drop the idle session.
+    fn keepalive(&mut self) -> impl Future<Output = Result<(), SonioxLiveErrors>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Tells the service that no more audio follows. It may still send the remaining
+    /// results before it closes the connection.
+    fn close(&mut self) -> impl Future<Output = Result<(), SonioxLiveErrors>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Waits for the next event from the service. An `Err` means the connection is gone.
+    fn recv_event(&mut self)
+    -> impl Future<Output = Result<BackendEvent, SonioxLiveErrors>> + Send;
+}
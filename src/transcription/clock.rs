@@ -0,0 +1,97 @@
+use crate::types::backend::TranscriptUpdate;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Capture position of the uploaded sample at `uploaded`; the following uploaded
+/// samples were captured right after it, up to the next anchor.
+#[derive(Debug, Clone, Copy)]
+struct Anchor {
+    uploaded: u64,
+    captured: u64,
+}
+
+#[derive(Debug, Default)]
+struct ClockState {
+    captured: u64,
+    uploaded: u64,
+    anchors: Vec<Anchor>,
+}
+
+impl ClockState {
+    fn captured_at(&self, uploaded: u64) -> u64 {
+        let index = self.anchors.partition_point(|a| a.uploaded <= uploaded);
+        match index.checked_sub(1).map(|i| self.anchors[i]) {
+            Some(anchor) => anchor.captured + (uploaded - anchor.uploaded),
+            None => uploaded,
+        }
+    }
+}
+
+/// Maps uploaded audio back to the moment it was captured.
+///
+/// Backend timestamps only count the audio a session received, so silence dropped
+/// by the voice gate or time spent paused would vanish from the timeline. Positions
+/// are interleaved samples of the wire format, counted from the start of capture.
+#[derive(Debug)]
+pub struct AudioClock {
+    state: Mutex<ClockState>,
+    samples_per_sec: u64,
+}
+
+impl AudioClock {
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        Self {
+            state: Mutex::new(ClockState::default()),
+            samples_per_sec: (sample_rate as u64 * channels as u64).max(1),
+        }
+    }
+
+    /// Counts a captured chunk and returns the position of its first sample.
+    pub fn capture(&self, len: usize) -> u64 {
+        let Ok(mut state) = self.state.lock() else {
+            return 0;
+        };
+        let position = state.captured;
+        state.captured += len as u64;
+        position
+    }
+
+    /// Moves the capture position on by time in which nothing was captured.
+    pub fn skip(&self, duration: Duration) {
+        let samples = duration.as_millis() as u64 * self.samples_per_sec / 1000;
+        if let Ok(mut state) = self.state.lock() {
+            state.captured += samples;
+        }
+    }
+
+    /// Notes that the chunk captured at `captured` was handed to the worker.
+    pub fn upload(&self, captured: u64, len: usize) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let uploaded = state.uploaded;
+        if state.anchors.is_empty() || state.captured_at(uploaded) != captured {
+            state.anchors.push(Anchor { uploaded, captured });
+        }
+        state.uploaded += len as u64;
+    }
+
+    /// Milliseconds since capture started of `ms` into a session whose first
+    /// sample was the uploaded sample at `session_start`.
+    pub fn capture_ms(&self, session_start: u64, ms: f64) -> f64 {
+        let offset = (ms.max(0.0) * self.samples_per_sec as f64 / 1000.0) as u64;
+        let Ok(state) = self.state.lock() else {
+            return ms;
+        };
+        let captured = state.captured_at(session_start + offset);
+        captured as f64 * 1000.0 / self.samples_per_sec as f64
+    }
+
+    /// Moves the token timestamps of a session onto the capture timeline.
+    pub fn stamp(&self, update: &mut TranscriptUpdate, session_start: u64) {
+        for token in &mut update.tokens {
+            token.start_ms = token.start_ms.map(|ms| self.capture_ms(session_start, ms));
+            token.end_ms = token.end_ms.map(|ms| self.capture_ms(session_start, ms));
+        }
+    }
+}
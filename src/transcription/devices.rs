@@ -0,0 +1,116 @@
+use crate::errors::SonioxLiveErrors;
+use cpal::traits::{DeviceTrait, HostTrait};
+use cpal::{Device, Host, SupportedStreamConfig};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceKind {
+    /// Output device captured in loopback mode.
+    Output,
+    Input,
+}
+
+impl std::fmt::Display for DeviceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+pub fn available_hosts() -> Vec<String> {
+    cpal::available_hosts()
+        .into_iter()
+        .map(|id| id.name().to_string())
+        .collect()
+}
+
+pub fn available_devices(host: Option<&str>, kind: DeviceKind) -> Vec<String> {
+    let Ok(host) = open_host(host) else {
+        return Vec::new();
+    };
+    let devices = match kind {
+        DeviceKind::Output => host.output_devices().map(|d| d.collect::<Vec<_>>()),
+        DeviceKind::Input => host.input_devices().map(|d| d.collect::<Vec<_>>()),
+    };
+    match devices {
+        Ok(devices) => devices.iter().filter_map(device_name).collect(),
+        Err(e) => {
+            tracing::warn!("Failed to enumerate audio devices: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Resolves the configured device, falling back to the host default when it's gone.
+pub(crate) fn select_device(
+    host: Option<&str>,
+    kind: DeviceKind,
+    name: Option<&str>,
+) -> Result<(Device, SupportedStreamConfig), SonioxLiveErrors> {
+    let host = open_host(host)?;
+    let found = match name {
+        Some(name) => find_device(&host, kind, name)?,
+        None => None,
+    };
+    let device = match (found, name) {
+        (Some(device), _) => device,
+        (None, Some(name)) => {
+            tracing::warn!("Audio device `{}` is not found, using default", name);
+            default_device(&host, kind)?
+        }
+        (None, None) => default_device(&host, kind)?,
+    };
+
+    let config = match kind {
+        DeviceKind::Output => device.default_output_config()?,
+        DeviceKind::Input => device.default_input_config()?,
+    };
+    tracing::info!(
+        "Capturing from `{}` ({}) with {:?}",
+        device_name(&device).unwrap_or_default(),
+        kind,
+        config
+    );
+    Ok((device, config))
+}
+
+fn open_host(name: Option<&str>) -> Result<Host, SonioxLiveErrors> {
+    let Some(name) = name else {
+        return Ok(cpal::default_host());
+    };
+    let id = cpal::available_hosts()
+        .into_iter()
+        .find(|id| id.name().eq_ignore_ascii_case(name));
+    match id {
+        Some(id) => Ok(cpal::host_from_id(id)?),
+        None => {
+            tracing::warn!("Audio host `{}` is not available, using default", name);
+            Ok(cpal::default_host())
+        }
+    }
+}
+
+fn find_device(
+    host: &Host,
+    kind: DeviceKind,
+    name: &str,
+) -> Result<Option<Device>, SonioxLiveErrors> {
+    let mut devices: Box<dyn Iterator<Item = Device>> = match kind {
+        DeviceKind::Output => Box::new(host.output_devices()?),
+        DeviceKind::Input => Box::new(host.input_devices()?),
+    };
+    Ok(devices.find(|d| device_name(d).as_deref() == Some(name)))
+}
+
+fn default_device(host: &Host, kind: DeviceKind) -> Result<Device, SonioxLiveErrors> {
+    match kind {
+        DeviceKind::Output => host
+            .default_output_device()
+            .ok_or(SonioxLiveErrors::NotFoundOutputDevice),
+        DeviceKind::Input => host
+            .default_input_device()
+            .ok_or(SonioxLiveErrors::NotFoundInputDevice),
+    }
+}
+
+fn device_name(device: &Device) -> Option<String> {
+    device.description().ok().map(|d| d.name().to_string())
+}
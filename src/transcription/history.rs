@@ -0,0 +1,288 @@
+use crate::errors::SonioxLiveErrors;
+use crate::types::backend::TranscriptUpdate;
+use crate::types::speakers::SpeakerRegistry;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MAX_ENTRY_LEN: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub session: u64,
+    pub timestamp: u64,
+    pub speaker: Option<String>,
+    pub text: String,
+}
+
+impl HistoryEntry {
+    pub fn matches(&self, query: &str) -> bool {
+        if query.is_empty() {
+            return true;
+        }
+        let query = query.to_lowercase();
+        self.text.to_lowercase().contains(&query)
+            || self
+                .speaker
+                .as_deref()
+                .is_some_and(|s| s.to_lowercase().contains(&query))
+    }
+}
+
+impl std::fmt::Display for HistoryEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.speaker {
+            Some(speaker) => write!(f, "[{}] {}", speaker, self.text.trim()),
+            None => write!(f, "{}", self.text.trim()),
+        }
+    }
+}
+
+/// Every final utterance of every session, unlike [`TranscriptionStore`] which only
+/// keeps what fits on screen.
+///
+/// Finished entries are appended to a JSON Lines file so previous sessions survive
+/// restarts. Speaker names live next to it, per session, because diarization ids
+/// are reassigned every session.
+///
+/// [`TranscriptionStore`]: crate::transcription::store::TranscriptionStore
+#[derive(Default)]
+pub struct TranscriptHistory {
+    entries: Vec<HistoryEntry>,
+    path: Option<PathBuf>,
+    saved: usize,
+    session: u64,
+    open: bool,
+    speakers: SpeakerRegistry,
+    saved_speakers: BTreeMap<u64, SpeakerRegistry>,
+}
+
+impl TranscriptHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads entries of previous sessions from `path` and keeps appending to it.
+    /// A missing file is not an error.
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, SonioxLiveErrors> {
+        let path = path.into();
+        let mut entries = Vec::new();
+        if path.exists() {
+            let content = std::fs::read_to_string(&path)?;
+            for line in content.lines().filter(|l| !l.trim().is_empty()) {
+                match serde_json::from_str(line) {
+                    Ok(entry) => entries.push(entry),
+                    Err(e) => tracing::warn!("Skipping broken history line: {}", e),
+                }
+            }
+        }
+        let speakers_path = speakers_path(&path);
+        let saved_speakers = if speakers_path.exists() {
+            let content = std::fs::read_to_string(&speakers_path)?;
+            serde_json::from_str(&content).unwrap_or_else(|e| {
+                tracing::warn!("Skipping broken speaker names: {}", e);
+                BTreeMap::new()
+            })
+        } else {
+            BTreeMap::new()
+        };
+        Ok(Self {
+            saved: entries.len(),
+            entries,
+            path: Some(path),
+            session: 0,
+            open: false,
+            speakers: SpeakerRegistry::default(),
+            saved_speakers,
+        })
+    }
+
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    pub fn session(&self) -> u64 {
+        self.session
+    }
+
+    /// Speakers of the current session.
+    pub fn speakers(&self) -> &SpeakerRegistry {
+        &self.speakers
+    }
+
+    pub fn speakers_mut(&mut self) -> &mut SpeakerRegistry {
+        &mut self.speakers
+    }
+
+    /// Speakers of any session, as they were when it was last written out.
+    pub fn session_speakers(&self, session: u64) -> Option<&SpeakerRegistry> {
+        if session == self.session {
+            return Some(&self.speakers);
+        }
+        self.saved_speakers.get(&session)
+    }
+
+    /// Formats an entry with the speaker names of its session.
+    pub fn describe(&self, entry: &HistoryEntry) -> String {
+        let speakers = self.session_speakers(entry.session);
+        match (&entry.speaker, speakers) {
+            (Some(id), Some(speakers)) => format!("[{}] {}", speakers.label(id), entry.text.trim()),
+            _ => entry.to_string(),
+        }
+    }
+
+    pub fn sessions(&self) -> usize {
+        let mut count = 0;
+        let mut last = None;
+        for entry in &self.entries {
+            if last != Some(entry.session) {
+                count += 1;
+                last = Some(entry.session);
+            }
+        }
+        count
+    }
+
+    pub fn search<'a>(&'a self, query: &'a str) -> impl Iterator<Item = &'a HistoryEntry> {
+        self.entries.iter().filter(move |e| e.matches(query))
+    }
+
+    pub fn begin_session(&mut self) {
+        self.close();
+        self.session = unix_now();
+        self.speakers = SpeakerRegistry::default();
+    }
+
+    pub fn record(&mut self, response: &TranscriptUpdate) {
+        // New diarization ids show up in the config window so they can be named.
+        for id in response.tokens.iter().filter_map(|t| t.speaker.as_deref()) {
+            self.speakers.register(id);
+        }
+        for token in response.tokens.iter().filter(|t| t.is_final) {
+            if token.is_endpoint() {
+                self.close();
+                continue;
+            }
+            if token.is_original() || token.text.is_empty() {
+                continue;
+            }
+            let needs_new = match self.entries.last() {
+                Some(last) if self.open => {
+                    last.speaker != token.speaker || last.text.len() > MAX_ENTRY_LEN
+                }
+                _ => true,
+            };
+            if needs_new {
+                self.persist(self.entries.len());
+                self.entries.push(HistoryEntry {
+                    session: self.session,
+                    timestamp: unix_now(),
+                    speaker: token.speaker.clone(),
+                    text: token.text.trim_start().to_string(),
+                });
+                self.open = true;
+            } else if let Some(last) = self.entries.last_mut() {
+                last.text.push_str(&token.text);
+            }
+        }
+    }
+
+    /// Ends the current utterance, e.g. after a reconnect, and writes it out.
+    pub fn close(&mut self) {
+        self.open = false;
+        self.persist(self.entries.len());
+        self.persist_speakers();
+    }
+
+    fn persist_speakers(&mut self) {
+        if self.session == 0
+            || self.speakers.is_empty()
+            || self.saved_speakers.get(&self.session) == Some(&self.speakers)
+        {
+            return;
+        }
+        self.saved_speakers
+            .insert(self.session, self.speakers.clone());
+        let Some(path) = &self.path else {
+            return;
+        };
+        let path = speakers_path(path);
+        let result = serde_json::to_vec_pretty(&self.saved_speakers)
+            .map_err(std::io::Error::from)
+            .and_then(|content| std::fs::write(&path, content));
+        if let Err(e) = result {
+            tracing::warn!("Failed to write speaker names to {:?}: {}", path, e);
+        }
+    }
+
+    fn persist(&mut self, upto: usize) {
+        let Some(path) = &self.path else {
+            self.saved = upto;
+            return;
+        };
+        if upto <= self.saved {
+            return;
+        }
+        let result = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| {
+                let mut buf = Vec::new();
+                for entry in &self.entries[self.saved..upto] {
+                    serde_json::to_writer(&mut buf, entry)?;
+                    buf.push(b'\n');
+                }
+                file.write_all(&buf)
+            });
+        match result {
+            Ok(()) => self.saved = upto,
+            Err(e) => tracing::warn!("Failed to write history to {:?}: {}", path, e),
+        }
+    }
+}
+
+impl Drop for TranscriptHistory {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// `history.jsonl` keeps its speaker names in `history.speakers.json`.
+fn speakers_path(path: &Path) -> PathBuf {
+    path.with_extension("speakers.json")
+}
+
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Formats a unix timestamp as `YYYY-MM-DD HH:MM` in UTC.
+pub fn format_unix(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let time = secs % 86_400;
+    // civil-from-days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60
+    )
+}
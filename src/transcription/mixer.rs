@@ -0,0 +1,44 @@
+use std::collections::VecDeque;
+
+/// Mixes two PCM streams that already share one format.
+///
+/// Loopback devices stop delivering callbacks while nothing plays, so a source
+/// that runs more than `max_latency` samples ahead is flushed against silence
+/// instead of waiting for the other one forever.
+pub struct AudioMixer {
+    queues: [VecDeque<i16>; 2],
+    gains: [f32; 2],
+    max_latency: usize,
+}
+
+impl AudioMixer {
+    pub fn new(gains: [f32; 2], max_latency: usize) -> Self {
+        Self {
+            queues: [VecDeque::new(), VecDeque::new()],
+            gains,
+            max_latency,
+        }
+    }
+
+    pub fn push(&mut self, source: usize, samples: &[i16]) {
+        self.queues[source].extend(samples);
+    }
+
+    pub fn drain(&mut self, output: &mut Vec<i16>) {
+        output.clear();
+        let [a, b] = &self.queues;
+        let longest = a.len().max(b.len());
+        let ready = a
+            .len()
+            .min(b.len())
+            .max(longest.saturating_sub(self.max_latency));
+
+        for _ in 0..ready {
+            let mut mixed = 0.0;
+            for (queue, gain) in self.queues.iter_mut().zip(self.gains) {
+                mixed += queue.pop_front().unwrap_or(0) as f32 * gain;
+            }
+            output.push(mixed.clamp(i16::MIN as f32, i16::MAX as f32) as i16);
+        }
+    }
+}
@@ -1,5 +1,13 @@
 pub mod audio;
+pub mod backend;
+pub mod clock;
+pub mod devices;
+pub mod history;
+pub mod mixer;
+pub mod recorder;
 pub mod replicas;
 pub mod service;
 pub mod store;
+pub mod transcript_log;
 pub mod utils;
+pub mod vad;
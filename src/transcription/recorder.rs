@@ -0,0 +1,198 @@
+use crate::errors::SonioxLiveErrors;
+use crate::types::backend::{TranscriptToken, TranscriptUpdate};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MAX_CUE_CHARS: usize = 84;
+const MIN_SENTENCE_CHARS: usize = 20;
+const MAX_GAP_MS: u64 = 1500;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptCue {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub speaker: Option<String>,
+    pub text: String,
+}
+
+/// Collects final tokens with their timing so a session can be exported as subtitles.
+///
+/// Token timestamps are expected on the capture timeline, which the worker maps
+/// them onto with an [`crate::transcription::clock::AudioClock`].
+pub struct TranscriptRecorder {
+    cues: Vec<TranscriptCue>,
+    endpoint: bool,
+}
+
+impl Default for TranscriptRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TranscriptRecorder {
+    pub fn new() -> Self {
+        Self {
+            cues: Vec::new(),
+            endpoint: false,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn cues(&self) -> &[TranscriptCue] {
+        &self.cues
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cues.is_empty()
+    }
+
+    pub fn record(&mut self, response: &TranscriptUpdate) {
+        for token in response.tokens.iter().filter(|t| t.is_final) {
+            if token.is_endpoint() {
+                self.endpoint = true;
+                continue;
+            }
+            if token.is_original() {
+                continue;
+            }
+            self.push_token(token);
+        }
+    }
+
+    fn push_token(&mut self, token: &TranscriptToken) {
+        if token.text.is_empty() {
+            return;
+        }
+        let start = token.start_ms.map(|ms| ms as u64);
+        let end = token.end_ms.map(|ms| ms as u64);
+
+        let after_endpoint = std::mem::take(&mut self.endpoint);
+        let needs_new = after_endpoint
+            || match self.cues.last() {
+                None => true,
+                Some(last) => {
+                    let text = last.text.trim_end();
+                    last.speaker != token.speaker
+                        || start.is_some_and(|s| s > last.end_ms + MAX_GAP_MS)
+                        || (token.text.starts_with(' ') && text.chars().count() >= MAX_CUE_CHARS)
+                        || (text.chars().count() >= MIN_SENTENCE_CHARS
+                            && text.ends_with(['.', '?', '!', '。', '？', '！']))
+                }
+            };
+
+        if needs_new {
+            let fallback = self.cues.last().map(|c| c.end_ms).unwrap_or(0);
+            let start = start.unwrap_or(fallback);
+            self.cues.push(TranscriptCue {
+                start_ms: start,
+                end_ms: end.unwrap_or(start).max(start),
+                speaker: token.speaker.clone(),
+                text: token.text.trim_start().to_string(),
+            });
+            return;
+        }
+
+        if let Some(cue) = self.cues.last_mut() {
+            cue.text.push_str(&token.text);
+            if let Some(end) = end {
+                cue.end_ms = cue.end_ms.max(end);
+            }
+        }
+    }
+
+    pub fn to_srt(&self) -> String {
+        let mut out = String::new();
+        for (i, cue) in self.cues.iter().enumerate() {
+            let _ = writeln!(out, "{}", i + 1);
+            let _ = writeln!(
+                out,
+                "{} --> {}",
+                format_timestamp(cue.start_ms, ','),
+                format_timestamp(cue.end_ms, ',')
+            );
+            match &cue.speaker {
+                Some(speaker) => {
+                    let _ = writeln!(out, "{}: {}", speaker, cue.text.trim());
+                }
+                None => {
+                    let _ = writeln!(out, "{}", cue.text.trim());
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn to_vtt(&self) -> String {
+        let mut out = String::from("WEBVTT\n\n");
+        for cue in &self.cues {
+            let _ = writeln!(
+                out,
+                "{} --> {}",
+                format_timestamp(cue.start_ms, '.'),
+                format_timestamp(cue.end_ms, '.')
+            );
+            match &cue.speaker {
+                Some(speaker) => {
+                    let _ = writeln!(out, "<v {}>{}", speaker, cue.text.trim());
+                }
+                None => {
+                    let _ = writeln!(out, "{}", cue.text.trim());
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Writes the transcript into `dir` as `session-<unix time>.<ext>` for each
+    /// requested format and returns the written paths.
+    pub fn export(
+        &self,
+        dir: impl AsRef<Path>,
+        srt: bool,
+        vtt: bool,
+    ) -> Result<Vec<PathBuf>, SonioxLiveErrors> {
+        if self.cues.is_empty() {
+            return Ok(Vec::new());
+        }
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        let mut written = Vec::new();
+        if srt {
+            let path = dir.join(format!("session-{}.srt", stamp));
+            std::fs::write(&path, self.to_srt())?;
+            written.push(path);
+        }
+        if vtt {
+            let path = dir.join(format!("session-{}.vtt", stamp));
+            std::fs::write(&path, self.to_vtt())?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+}
+
+fn format_timestamp(ms: u64, separator: char) -> String {
+    let hours = ms / 3_600_000;
+    let minutes = ms / 60_000 % 60;
+    let seconds = ms / 1000 % 60;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        hours,
+        minutes,
+        seconds,
+        separator,
+        ms % 1000
+    )
+}
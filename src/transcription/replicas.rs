@@ -1,25 +1,72 @@
 use crate::transcription::store::TranscriptionStore;
+use crate::types::languages::LanguageHint;
+use crate::types::subtitles::TokenSpan;
 
 pub struct VisualReplica<'a> {
     pub speaker: Option<&'a str>,
+    pub direction: Option<(LanguageHint, LanguageHint)>,
+    pub language: Option<LanguageHint>,
     pub elements: Vec<TextElement<'a>>,
+    pub originals: Vec<TextElement<'a>>,
 }
 
 pub struct TextElement<'a> {
     pub text: &'a str,
     pub is_interim: bool,
+    pub spans: &'a [TokenSpan],
+}
+
+impl<'a> TextElement<'a> {
+    /// Splits the text by token so each piece carries its confidence. Text that
+    /// isn't covered by a token, like the reconnect separator, counts as certain.
+    pub fn segments(&self) -> Vec<(&'a str, f32)> {
+        let mut segments = Vec::with_capacity(self.spans.len() + 1);
+        let mut cursor = 0;
+        for span in self.spans {
+            let end = span.end.min(self.text.len());
+            if span.start < cursor || span.start >= end {
+                continue;
+            }
+            if let Some(gap) = self.text.get(cursor..span.start).filter(|g| !g.is_empty()) {
+                segments.push((gap, 1.0));
+            }
+            if let Some(token) = self.text.get(span.start..end) {
+                segments.push((token, span.confidence));
+                cursor = end;
+            }
+        }
+        if let Some(rest) = self.text.get(cursor..).filter(|r| !r.is_empty()) {
+            segments.push((rest, 1.0));
+        }
+        segments
+    }
 }
 
 impl<'a> VisualReplica<'a> {
     pub fn new(speaker: Option<&'a str>) -> Self {
         Self {
             speaker,
+            direction: None,
+            language: None,
             elements: Vec::new(),
+            originals: Vec::new(),
         }
     }
 
-    pub fn add_text(&mut self, text: &'a str, is_interim: bool) {
-        self.elements.push(TextElement { text, is_interim });
+    pub fn add_text(&mut self, text: &'a str, is_interim: bool, spans: &'a [TokenSpan]) {
+        self.elements.push(TextElement {
+            text,
+            is_interim,
+            spans,
+        });
+    }
+
+    pub fn add_original(&mut self, text: &'a str, is_interim: bool) {
+        self.originals.push(TextElement {
+            text,
+            is_interim,
+            spans: &[],
+        });
     }
 }
 
@@ -28,16 +75,23 @@ pub fn prepare_replicas(store: &'_ TranscriptionStore) -> Vec<VisualReplica<'_>>
     let final_blocks = store.blocks.iter().map(|b| (b, false));
     let interim_blocks = store.interim_blocks.iter().map(|b| (b, true));
     let all_blocks = final_blocks.chain(interim_blocks);
+    let show_original = store.show_original();
 
     for (block, is_interim) in all_blocks {
-        if block.text.is_empty() {
+        if block.text.is_empty() && (!show_original || block.original.is_empty()) {
             continue;
         }
 
         let speaker = block.speaker.as_deref();
+        let direction = block.direction();
+        let language = block.spoken_language();
         let should_merge = replicas
             .last()
-            .map(|last| last.speaker == speaker)
+            .map(|last| {
+                last.speaker == speaker
+                    && compatible(last.direction, direction)
+                    && compatible(last.language, language)
+            })
             .unwrap_or(false);
 
         if !should_merge {
@@ -48,10 +102,23 @@ pub fn prepare_replicas(store: &'_ TranscriptionStore) -> Vec<VisualReplica<'_>>
             tracing::warn!("Replicas hadn't last element...");
             continue;
         };
+        target.direction = target.direction.or(direction);
+        target.language = target.language.or(language);
         if !block.text.is_empty() {
-            target.add_text(&block.text, is_interim);
+            target.add_text(&block.text, is_interim, &block.spans);
+        }
+        if show_original && !block.original.is_empty() {
+            target.add_original(&block.original, is_interim);
         }
     }
 
     replicas
 }
+
+/// Blocks whose language isn't known yet, e.g. interim text, join their neighbours.
+fn compatible<T: PartialEq>(last: Option<T>, next: Option<T>) -> bool {
+    match (last, next) {
+        (Some(last), Some(next)) => last == next,
+        _ => true,
+    }
+}
@@ -1,38 +1,60 @@
 use crate::errors::SonioxLiveErrors;
 use crate::settings::SettingsApp;
+use crate::soniox::backend::SonioxBackend;
+use crate::soniox::backlog::AudioBacklog;
 use crate::soniox::request::create_request;
-use crate::soniox::worker::SonioxWorker;
+use crate::soniox::worker::{SonioxWorker, WorkerTask};
 use crate::transcription::audio::AudioSession;
 use crate::types::audio::AudioSample;
-use crate::types::events::SonioxEvent;
+use crate::types::backend::BackendKind;
+use crate::types::events::{SonioxEvent, WorkerCommand};
 use eframe::egui::Context;
-use tokio::sync::mpsc::{Receiver, channel};
+use std::time::Instant;
+use tokio::sync::mpsc::{Receiver, Sender, channel};
 
 pub struct TranscriptionService {
-    pub(crate) _audio: AudioSession,
+    pub(crate) audio: AudioSession,
     pub receiver: Receiver<SonioxEvent>,
-    handle: tokio::task::JoinHandle<()>,
+    commands: Sender<WorkerCommand>,
+    worker: WorkerTask,
+    paused_at: Option<Instant>,
 }
 
 impl TranscriptionService {
     pub fn start(ctx: Context, settings_app: &SettingsApp) -> Result<Self, SonioxLiveErrors> {
+        settings_app.validate()?;
         let (tx_worker, mut rx_worker) = channel::<SonioxEvent>(128);
         let (tx_event, rx_event) = channel::<SonioxEvent>(128);
         let (tx_audio, rx_audio) = channel::<AudioSample>(256);
         let (tx_recycle, rx_recycle) = channel::<AudioSample>(256);
+        let (tx_command, rx_command) = channel::<WorkerCommand>(8);
 
-        let tx_worker_2 = tx_worker.clone();
-        let worker = SonioxWorker::new(rx_audio, tx_recycle, tx_worker_2);
-        let audio = AudioSession::open(tx_audio, rx_recycle)?;
-        let request = create_request(settings_app, audio.config())?;
-        audio.play()?;
-
-        let handle = tokio::spawn(async move {
-            if let Err(e) = worker.run(&request).await {
-                tracing::error!("WebSocket error: {:?}", e);
-                let _ = tx_worker.send(SonioxEvent::Error(e)).await;
+        let audio = AudioSession::open(settings_app, tx_audio, rx_recycle, tx_worker.clone())?;
+        let backlog = AudioBacklog::with_duration(
+            settings_app.reconnect_buffer_secs(),
+            audio.config().sample_rate,
+            audio.config().channels,
+        );
+        let worker = SonioxWorker::new(
+            rx_audio,
+            rx_command,
+            tx_recycle,
+            tx_worker.clone(),
+            settings_app.retry_policy().clone(),
+            backlog,
+        )
+        .with_clock(audio.clock().clone());
+        let worker = match settings_app.backend() {
+            BackendKind::Soniox => {
+                let request = create_request(settings_app, audio.config())?;
+                audio.play()?;
+                WorkerTask::spawn(
+                    worker,
+                    SonioxBackend::new(settings_app, request),
+                    tx_worker.clone(),
+                )
             }
-        });
+        };
         tokio::spawn(async move {
             while let Some(event) = rx_worker.recv().await {
                 if tx_event.send(event).await.is_err() {
@@ -43,17 +65,56 @@ impl TranscriptionService {
         });
 
         Ok(Self {
-            _audio: audio,
-            handle,
+            audio,
+            worker,
+            commands: tx_command,
             receiver: rx_event,
+            paused_at: None,
         })
     }
 
-    pub fn listen() {}
-}
+    /// Asks the backend to finalize the interim text now instead of at the next pause.
+    pub fn finalize(&self) {
+        if let Err(e) = self.commands.try_send(WorkerCommand::Finalize) {
+            tracing::warn!("Finalize request dropped: {}", e);
+        }
+    }
 
-impl Drop for TranscriptionService {
-    fn drop(&mut self) {
-        self.handle.abort();
+    /// Reconnects with a request built from `settings_app`. Audio capture keeps running
+    /// and whatever is captured meanwhile is replayed into the new session.
+    pub fn restart_worker(&mut self, settings_app: &SettingsApp) -> Result<(), SonioxLiveErrors> {
+        settings_app.validate()?;
+        let backend = match settings_app.backend() {
+            BackendKind::Soniox => SonioxBackend::new(
+                settings_app,
+                create_request(settings_app, self.audio.config())?,
+            ),
+        };
+        self.worker
+            .restart(backend, settings_app.retry_policy().clone())?;
+        tracing::info!("Transcription worker restarted with new settings");
+        Ok(())
     }
+
+    /// Stops or resumes capture. The worker keeps the session alive with keepalives meanwhile.
+    /// The paused time still counts towards the transcript timeline.
+    pub fn toggle_pause(&mut self) -> Result<bool, SonioxLiveErrors> {
+        match self.paused_at.take() {
+            Some(paused_at) => {
+                self.audio.play()?;
+                self.audio.clock().skip(paused_at.elapsed());
+            }
+            None => {
+                self.audio.pause()?;
+                self.paused_at = Some(Instant::now());
+            }
+        }
+        Ok(self.is_paused())
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    pub fn listen() {}
 }
@@ -1,14 +1,24 @@
-use crate::types::soniox::SonioxTranscriptionResponse;
+use crate::types::backend::{TranscriptToken, TranscriptUpdate};
 use crate::types::subtitles::SubtitleBlock;
 use eframe::egui::Context;
 use std::collections::VecDeque;
 use std::time::{Duration, Instant};
+use unicode_segmentation::UnicodeSegmentation;
+
+const DEFAULT_MAX_CHARS: usize = 200;
 
 pub struct TranscriptionStore {
     pub blocks: VecDeque<SubtitleBlock>,
     pub interim_blocks: Vec<SubtitleBlock>,
     max_blocks: usize,
+    max_chars: usize,
+    show_original: bool,
     last_activity: Option<Instant>,
+    /// Block the next final translation goes into. Translations lag behind their
+    /// originals, so this may point before the last block.
+    translation_cursor: usize,
+    /// An original opened a block past the cursor since the last translation.
+    originals_ahead: bool,
 }
 
 impl TranscriptionStore {
@@ -17,39 +27,42 @@ impl TranscriptionStore {
             blocks: VecDeque::with_capacity(max_blocks),
             interim_blocks: Vec::with_capacity(max_blocks),
             max_blocks,
+            max_chars: DEFAULT_MAX_CHARS,
+            show_original: false,
             last_activity: None,
+            translation_cursor: 0,
+            originals_ahead: false,
         }
     }
 
-    pub fn update(&mut self, response: SonioxTranscriptionResponse) {
+    pub fn update(&mut self, response: TranscriptUpdate) {
         self.interim_blocks.clear();
         let mut current_interim_block: Option<SubtitleBlock> = None;
 
         for token in &response.tokens {
             tracing::debug!("{:?}", token);
-            if token.translation_status.as_deref() == Some("original") {
+            if token.is_endpoint() {
+                if let Some(block) = self.blocks.back_mut() {
+                    block.closed = true;
+                }
+                continue;
+            }
+            // Hidden originals still split the blocks their translation lands in,
+            // only their interim text has no use.
+            if token.is_original() && !token.is_final && !self.show_original {
                 continue;
             }
 
             if token.is_final {
-                let speaker = token.speaker.clone();
-                let needs_new = match self.blocks.back() {
-                    Some(last) => last.speaker != speaker || last.text.len() > 200,
-                    None => true,
-                };
-
-                if needs_new {
-                    self.blocks.push_back(SubtitleBlock::new(speaker.clone()));
-                    self.pop_if_overflow();
-                }
-
-                if let Some(block) = self.blocks.back_mut() {
-                    block.text.push_str(&token.text);
+                if token.is_translation() {
+                    self.push_translation(token);
+                } else {
+                    self.push_final(token);
                 }
             } else {
                 let speaker = token.speaker.clone();
                 let speaker_changed = match &current_interim_block {
-                    Some(block) => block.speaker != speaker,
+                    Some(block) => block.speaker != speaker || changes_language(block, token),
                     None => true,
                 };
 
@@ -58,10 +71,10 @@ impl TranscriptionStore {
                         self.interim_blocks.push(block);
                     }
                     let mut new_block = SubtitleBlock::new(speaker);
-                    new_block.text.push_str(&token.text);
+                    new_block.push(token);
                     current_interim_block = Some(new_block);
                 } else if let Some(block) = &mut current_interim_block {
-                    block.text.push_str(&token.text);
+                    block.push(token);
                 }
             }
         }
@@ -74,25 +87,90 @@ impl TranscriptionStore {
         }
     }
 
+    fn push_final(&mut self, token: &TranscriptToken) {
+        let needs_new = match self.blocks.back() {
+            Some(last) => starts_block(last, token, self.max_chars),
+            None => true,
+        };
+        if needs_new {
+            self.blocks
+                .push_back(SubtitleBlock::new(token.speaker.clone()));
+            self.pop_if_overflow();
+        }
+        if token.is_original() && self.blocks.len() > self.translation_cursor + 1 {
+            self.originals_ahead = true;
+        }
+        if let Some(block) = self.blocks.back_mut() {
+            block.push(token);
+        }
+    }
+
+    /// Puts a translation into the block holding its original, so both lines of
+    /// a replica stay aligned. Without an original waiting, it opens a new block.
+    fn push_translation(&mut self, token: &TranscriptToken) {
+        while let Some(block) = self.blocks.get(self.translation_cursor) {
+            if joins_translation(block, token, self.originals_ahead, self.max_chars) {
+                break;
+            }
+            self.translation_cursor += 1;
+        }
+        if self.translation_cursor >= self.blocks.len() {
+            self.blocks
+                .push_back(SubtitleBlock::new(token.speaker.clone()));
+            self.pop_if_overflow();
+            self.translation_cursor = self.blocks.len() - 1;
+        }
+        if let Some(block) = self.blocks.get_mut(self.translation_cursor) {
+            block.push(token);
+        }
+        self.originals_ahead = false;
+    }
+
     pub fn ensure_separator(&mut self) {
         for block in self.interim_blocks.drain(..) {
             let mut new_block = SubtitleBlock::new(block.speaker);
             new_block.text = block.text;
+            new_block.original = block.original;
+            new_block.language = block.language;
+            new_block.source_language = block.source_language;
+            new_block.spans = block.spans;
             self.blocks.push_back(new_block);
         }
 
         self.pop_if_overflow();
+        // A new session never translates what the old one left untranslated.
+        self.translation_cursor = self.blocks.len();
+        self.originals_ahead = false;
         if let Some(block) = self.blocks.back_mut() {
             if block.text.is_empty() {
                 return;
             }
             let trimmed_len = block.text.trim_end().len();
             block.text.truncate(trimmed_len);
+            block.spans.retain_mut(|span| {
+                span.end = span.end.min(trimmed_len);
+                span.start < span.end
+            });
             block.text.push_str("...    ");
             self.last_activity = Some(Instant::now());
         }
     }
 
+    /// Shows the source text of translated tokens next to the translation instead
+    /// of hiding it.
+    pub fn set_show_original(&mut self, show_original: bool) {
+        self.show_original = show_original;
+    }
+
+    pub fn show_original(&self) -> bool {
+        self.show_original
+    }
+
+    /// Blocks longer than this many characters are split at the next word.
+    pub fn set_max_chars(&mut self, max_chars: usize) {
+        self.max_chars = max_chars.max(1);
+    }
+
     pub fn max_blocks(&self) -> usize {
         self.max_blocks
     }
@@ -100,6 +178,7 @@ impl TranscriptionStore {
     pub fn pop_if_overflow(&mut self) {
         while self.blocks.len() > self.max_blocks {
             self.blocks.pop_front();
+            self.translation_cursor = self.translation_cursor.saturating_sub(1);
         }
     }
 
@@ -112,13 +191,19 @@ impl TranscriptionStore {
         self.last_activity
     }
 
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+        self.interim_blocks.clear();
+        self.last_activity = None;
+        self.translation_cursor = 0;
+        self.originals_ahead = false;
+    }
+
     pub fn clear_if_silent(&mut self, timeout: Duration) {
         if let Some(last_activity) = self.last_activity
             && last_activity.elapsed() >= timeout
         {
-            self.blocks.clear();
-            self.interim_blocks.clear();
-            self.last_activity = None;
+            self.clear();
         }
     }
 
@@ -131,3 +216,83 @@ impl TranscriptionStore {
         }
     }
 }
+
+/// A block never mixes languages: with language identification a speaker may
+/// switch mid-sentence, and in two-way translation both directions can come
+/// from the same speaker.
+fn changes_language(block: &SubtitleBlock, token: &TranscriptToken) -> bool {
+    match (
+        block.spoken_language(),
+        token.source_language.or(token.language),
+    ) {
+        (Some(current), Some(next)) => current != next,
+        _ => false,
+    }
+}
+
+/// A block ends at an endpoint or a finished sentence. Originals are measured
+/// by their own text, so the translation that follows can land in the same block.
+fn starts_block(last: &SubtitleBlock, token: &TranscriptToken, max_chars: usize) -> bool {
+    let text = if token.is_original() {
+        // A block that already holds text without an original isn't waiting for one.
+        if last.original.is_empty() && !last.text.is_empty() {
+            return true;
+        }
+        &last.original
+    } else {
+        &last.text
+    };
+    last.speaker != token.speaker
+        || last.closed
+        || changes_language(last, token)
+        || ends_sentence(text)
+        || exceeds_limit(text, &token.text, max_chars)
+}
+
+/// A translation continues its block until the sentence ends or an original has
+/// already moved on to the next block. A block without translated text yet is
+/// waiting for exactly this token.
+fn joins_translation(
+    block: &SubtitleBlock,
+    token: &TranscriptToken,
+    originals_ahead: bool,
+    max_chars: usize,
+) -> bool {
+    if block.speaker != token.speaker || changes_language(block, token) {
+        return false;
+    }
+    if block.text.is_empty() {
+        return true;
+    }
+    !originals_ahead
+        && !ends_sentence(&block.text)
+        && !exceeds_limit(&block.text, &token.text, max_chars)
+}
+
+fn ends_sentence(text: &str) -> bool {
+    text.trim_end()
+        .ends_with(['.', '?', '!', '…', '。', '？', '！'])
+}
+
+/// Past `max_chars` graphemes a block is split before the next word. Scripts
+/// without spaces are split anyway past half as much again, but never inside a
+/// grapheme cluster.
+fn exceeds_limit(text: &str, next: &str, max_chars: usize) -> bool {
+    let len = text.graphemes(true).count();
+    if len < max_chars {
+        return false;
+    }
+    if next.starts_with(char::is_whitespace) {
+        return true;
+    }
+    if len < max_chars + max_chars / 2 {
+        return false;
+    }
+    let (Some(last), Some(first)) = (
+        text.graphemes(true).next_back(),
+        next.graphemes(true).next(),
+    ) else {
+        return true;
+    };
+    format!("{}{}", last, first).graphemes(true).count() > 1
+}
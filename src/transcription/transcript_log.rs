@@ -0,0 +1,230 @@
+use crate::errors::SonioxLiveErrors;
+use crate::transcription::history::{format_unix, unix_now};
+use crate::types::backend::{TranscriptToken, TranscriptUpdate};
+use crate::types::languages::LanguageHint;
+use crate::types::transcript::TranscriptLogFormat;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const MAX_UTTERANCE_LEN: usize = 500;
+const MAX_PENDING: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LoggedUtterance {
+    pub timestamp: u64,
+    pub speaker: Option<String>,
+    pub language: Option<LanguageHint>,
+    pub source_language: Option<LanguageHint>,
+    pub text: String,
+    pub translation: Option<String>,
+    #[serde(skip)]
+    translated: bool,
+    #[serde(skip)]
+    ended: bool,
+}
+
+impl LoggedUtterance {
+    fn new(speaker: Option<String>) -> Self {
+        Self {
+            timestamp: unix_now(),
+            speaker,
+            language: None,
+            source_language: None,
+            text: String::new(),
+            translation: None,
+            translated: false,
+            ended: false,
+        }
+    }
+
+    fn is_closed(&self, speaker: &Option<String>) -> bool {
+        self.ended
+            || &self.speaker != speaker
+            || self.text.len() > MAX_UTTERANCE_LEN
+            || ends_sentence(&self.text)
+    }
+
+    /// Originals run ahead of their translation, so an utterance waits until the
+    /// translation of its sentence has arrived too.
+    fn awaits_translation(&self) -> bool {
+        self.translated && !self.translation.as_deref().is_some_and(ends_sentence)
+    }
+}
+
+/// Appends every finalized utterance to a file so other tools can consume the
+/// transcript while the overlay runs.
+pub struct TranscriptLog<W: Write = File> {
+    writer: W,
+    format: TranscriptLogFormat,
+    pending: VecDeque<LoggedUtterance>,
+}
+
+impl TranscriptLog<File> {
+    /// Opens a new file for this session next to `path`, e.g. `transcripts/log`
+    /// becomes `transcripts/log-<session>.jsonl`.
+    pub fn create(
+        path: impl AsRef<Path>,
+        format: TranscriptLogFormat,
+        session: u64,
+    ) -> Result<(Self, PathBuf), SonioxLiveErrors> {
+        let path = session_path(path.as_ref(), format, session);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = File::create(&path)?;
+        Ok((Self::new(file, format), path))
+    }
+}
+
+impl<W: Write> TranscriptLog<W> {
+    pub fn new(writer: W, format: TranscriptLogFormat) -> Self {
+        Self {
+            writer,
+            format,
+            pending: VecDeque::new(),
+        }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    pub fn record(&mut self, response: &TranscriptUpdate) -> Result<(), SonioxLiveErrors> {
+        for token in response.tokens.iter().filter(|t| t.is_final) {
+            if token.is_endpoint() {
+                if let Some(last) = self.pending.back_mut() {
+                    last.ended = true;
+                }
+                continue;
+            }
+            if token.text.is_empty() {
+                continue;
+            }
+            if token.is_translation() {
+                self.push_translation(token)?;
+            } else {
+                self.push_original(token)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn push_original(&mut self, token: &TranscriptToken) -> Result<(), SonioxLiveErrors> {
+        if self
+            .pending
+            .back()
+            .is_none_or(|last| last.is_closed(&token.speaker))
+        {
+            self.pending
+                .push_back(LoggedUtterance::new(token.speaker.clone()));
+        }
+        if let Some(current) = self.pending.back_mut() {
+            if current.text.is_empty() {
+                current.text.push_str(token.text.trim_start());
+            } else {
+                current.text.push_str(&token.text);
+            }
+            current.language = current.language.or(token.language);
+            current.translated |= token.is_original();
+        }
+        self.write_ready()
+    }
+
+    fn push_translation(&mut self, token: &TranscriptToken) -> Result<(), SonioxLiveErrors> {
+        if self.pending.is_empty() {
+            self.pending
+                .push_back(LoggedUtterance::new(token.speaker.clone()));
+        }
+        let len = self.pending.len();
+        let target = self
+            .pending
+            .iter_mut()
+            .enumerate()
+            .find(|(i, u)| u.awaits_translation() || *i == len - 1)
+            .map(|(_, u)| u);
+        if let Some(current) = target {
+            current.translated = true;
+            current.source_language = current.source_language.or(token.source_language);
+            match &mut current.translation {
+                Some(translation) => translation.push_str(&token.text),
+                None => current.translation = Some(token.text.trim_start().to_string()),
+            }
+        }
+        self.write_ready()
+    }
+
+    /// Writes utterances that can't change anymore: a newer one has started and
+    /// the translation, if any, is complete.
+    fn write_ready(&mut self) -> Result<(), SonioxLiveErrors> {
+        while self.pending.len() > 1 {
+            let stalled = self.pending.len() > MAX_PENDING;
+            match self.pending.front() {
+                Some(front) if stalled || !front.awaits_translation() => {
+                    if let Some(utterance) = self.pending.pop_front() {
+                        self.write(utterance)?;
+                    }
+                }
+                _ => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes out the pending utterance, e.g. when the connection drops or the
+    /// session ends.
+    pub fn flush(&mut self) -> Result<(), SonioxLiveErrors> {
+        while let Some(utterance) = self.pending.pop_front() {
+            self.write(utterance)?;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, mut utterance: LoggedUtterance) -> Result<(), SonioxLiveErrors> {
+        utterance.text.truncate(utterance.text.trim_end().len());
+        if let Some(translation) = &mut utterance.translation {
+            translation.truncate(translation.trim_end().len());
+        }
+        match self.format {
+            TranscriptLogFormat::JsonLines => {
+                serde_json::to_writer(&mut self.writer, &utterance)?;
+                self.writer.write_all(b"\n")?;
+            }
+            TranscriptLogFormat::PlainText => {
+                write!(self.writer, "[{}] ", format_unix(utterance.timestamp))?;
+                if let Some(speaker) = &utterance.speaker {
+                    write!(self.writer, "Speaker {}: ", speaker)?;
+                }
+                writeln!(self.writer, "{}", utterance.text)?;
+                if let Some(translation) = &utterance.translation {
+                    writeln!(self.writer, "    -> {}", translation)?;
+                }
+            }
+        }
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+impl<W: Write> Drop for TranscriptLog<W> {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            tracing::warn!("Failed to flush transcript log: {}", e);
+        }
+    }
+}
+
+pub fn session_path(path: &Path, format: TranscriptLogFormat, session: u64) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("transcript");
+    path.with_file_name(format!("{}-{}.{}", stem, session, format.extension()))
+}
+
+fn ends_sentence(text: &str) -> bool {
+    text.trim_end()
+        .ends_with(['.', '?', '!', '…', '。', '？', '！'])
+}
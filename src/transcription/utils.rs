@@ -1,8 +1,320 @@
+use std::f64::consts::PI;
+
 const SCALE: f32 = i16::MAX as f32;
 
-pub fn convert_audio_chunk(input: &[f32], output: &mut Vec<i16>) {
+/// Device sample formats the capture stream can be built with.
+pub trait ToPcm16: cpal::SizedSample + Send + 'static {
+    fn to_pcm16(self) -> i16;
+}
+
+impl ToPcm16 for f32 {
+    fn to_pcm16(self) -> i16 {
+        (self.clamp(-1.0, 1.0) * SCALE) as i16
+    }
+}
+
+impl ToPcm16 for f64 {
+    fn to_pcm16(self) -> i16 {
+        (self.clamp(-1.0, 1.0) * SCALE as f64) as i16
+    }
+}
+
+impl ToPcm16 for i8 {
+    fn to_pcm16(self) -> i16 {
+        (self as i16) << 8
+    }
+}
+
+impl ToPcm16 for u8 {
+    fn to_pcm16(self) -> i16 {
+        ((self as i16) - 128) << 8
+    }
+}
+
+impl ToPcm16 for i16 {
+    fn to_pcm16(self) -> i16 {
+        self
+    }
+}
+
+impl ToPcm16 for u16 {
+    fn to_pcm16(self) -> i16 {
+        (self as i32 - 32768) as i16
+    }
+}
+
+impl ToPcm16 for i32 {
+    fn to_pcm16(self) -> i16 {
+        (self >> 16) as i16
+    }
+}
+
+impl ToPcm16 for u32 {
+    fn to_pcm16(self) -> i16 {
+        ((self >> 16) as i32 - 32768) as i16
+    }
+}
+
+pub fn convert_audio_chunk<T: ToPcm16>(input: &[T], output: &mut Vec<i16>) {
     if !output.is_empty() {
         output.clear();
     }
-    output.extend(input.iter().map(|&s| (s.clamp(-1.0, 1.0) * SCALE) as i16));
+    output.extend(input.iter().map(|&s| s.to_pcm16()));
+}
+
+/// Windowed-sinc low-pass run before downsampling, so content above the output
+/// Nyquist frequency doesn't fold back into the speech band.
+struct LowPass {
+    taps: Vec<f64>,
+    channels: usize,
+    /// Frames of the previous chunk the filter still reaches back to, followed by
+    /// the current chunk while it's being filtered.
+    window: Vec<i16>,
+}
+
+impl LowPass {
+    /// Cuts off at 90% of the output Nyquist frequency. The filter is long enough for
+    /// the transition band to end right at it, which takes more taps the bigger the
+    /// rate ratio.
+    fn new(from_rate: u32, to_rate: u32, channels: u16) -> Self {
+        let ratio = to_rate as f64 / from_rate as f64;
+        let cutoff = 0.45 * ratio;
+        let len = (3.3 / (0.1 * ratio)).ceil() as usize | 1;
+        let middle = (len / 2) as f64;
+        let mut taps: Vec<f64> = (0..len)
+            .map(|i| {
+                let n = i as f64 - middle;
+                let sinc = if n == 0.0 {
+                    2.0 * cutoff
+                } else {
+                    (2.0 * PI * cutoff * n).sin() / (PI * n)
+                };
+                let hamming = 0.54 - 0.46 * (2.0 * PI * i as f64 / (len - 1) as f64).cos();
+                sinc * hamming
+            })
+            .collect();
+        let gain: f64 = taps.iter().sum();
+        taps.iter_mut().for_each(|tap| *tap /= gain);
+        Self {
+            taps,
+            channels: channels.max(1) as usize,
+            window: Vec::new(),
+        }
+    }
+
+    fn process(&mut self, input: &[i16], output: &mut Vec<i16>) {
+        output.clear();
+        let channels = self.channels;
+        let frames = input.len() / channels;
+        if frames == 0 {
+            return;
+        }
+        let reach = self.taps.len() - 1;
+        if self.window.is_empty() {
+            // Starts as if the first frame had always been there, so there's no click.
+            let first = &input[..channels];
+            self.window
+                .extend((0..reach).flat_map(|_| first.iter().copied()));
+        }
+        self.window.extend_from_slice(&input[..frames * channels]);
+
+        for frame in 0..frames {
+            for c in 0..channels {
+                let sum: f64 = self
+                    .taps
+                    .iter()
+                    .enumerate()
+                    .map(|(k, tap)| tap * self.window[(frame + k) * channels + c] as f64)
+                    .sum();
+                output.push(sum.round().clamp(i16::MIN as f64, i16::MAX as f64) as i16);
+            }
+        }
+        self.window.drain(..frames * channels);
+    }
+}
+
+/// Converts interleaved PCM between channel layouts and sample rates.
+/// Keeps interpolation state between calls, so one instance serves one stream.
+pub struct AudioConverter {
+    from_rate: u32,
+    from_channels: u16,
+    to_rate: u32,
+    to_channels: u16,
+    position: f64,
+    previous: Option<Vec<i16>>,
+    remixed: Vec<i16>,
+    /// Only set when downsampling.
+    low_pass: Option<LowPass>,
+    filtered: Vec<i16>,
+}
+
+impl AudioConverter {
+    pub fn new(from_rate: u32, from_channels: u16, to_rate: u32, to_channels: u16) -> Self {
+        Self {
+            from_rate,
+            from_channels: from_channels.max(1),
+            to_rate,
+            to_channels: to_channels.max(1),
+            position: 0.0,
+            previous: None,
+            remixed: Vec::new(),
+            low_pass: (to_rate < from_rate).then(|| LowPass::new(from_rate, to_rate, to_channels)),
+            filtered: Vec::new(),
+        }
+    }
+
+    pub fn is_passthrough(&self) -> bool {
+        self.from_rate == self.to_rate && self.from_channels == self.to_channels
+    }
+
+    pub fn process(&mut self, input: &[i16], output: &mut Vec<i16>) {
+        output.clear();
+        if self.is_passthrough() {
+            output.extend_from_slice(input);
+            return;
+        }
+
+        let mut remixed = std::mem::take(&mut self.remixed);
+        remix(input, self.from_channels, self.to_channels, &mut remixed);
+        if self.from_rate == self.to_rate {
+            std::mem::swap(output, &mut remixed);
+        } else if let Some(low_pass) = &mut self.low_pass {
+            let mut filtered = std::mem::take(&mut self.filtered);
+            low_pass.process(&remixed, &mut filtered);
+            self.resample(&filtered, output);
+            self.filtered = filtered;
+        } else {
+            self.resample(&remixed, output);
+        }
+        self.remixed = remixed;
+    }
+
+    /// Linear interpolation over the chunk, with the last frame of the previous
+    /// chunk as the left neighbour of the first one.
+    fn resample(&mut self, input: &[i16], output: &mut Vec<i16>) {
+        let channels = self.to_channels as usize;
+        let frames = input.len() / channels;
+        if frames == 0 {
+            return;
+        }
+        let previous = self
+            .previous
+            .take()
+            .unwrap_or_else(|| input[..channels].to_vec());
+        let frame = |i: isize, c: usize| -> f64 {
+            if i < 0 {
+                previous[c] as f64
+            } else {
+                input[i as usize * channels + c] as f64
+            }
+        };
+
+        let step = self.from_rate as f64 / self.to_rate as f64;
+        let mut t = self.position;
+        while t < (frames - 1) as f64 {
+            let left = t.floor();
+            let frac = t - left;
+            let left = left as isize;
+            for c in 0..channels {
+                let a = frame(left, c);
+                let b = frame(left + 1, c);
+                output.push((a + (b - a) * frac).round() as i16);
+            }
+            t += step;
+        }
+
+        self.position = t - frames as f64;
+        self.previous = Some(input[(frames - 1) * channels..frames * channels].to_vec());
+    }
+}
+
+pub fn remix(input: &[i16], from_channels: u16, to_channels: u16, output: &mut Vec<i16>) {
+    output.clear();
+    let from = from_channels.max(1) as usize;
+    let to = to_channels.max(1) as usize;
+    if from == to {
+        output.extend_from_slice(input);
+        return;
+    }
+
+    for frame in input.chunks_exact(from) {
+        let mono = frame.iter().map(|&s| s as i32).sum::<i32>() / from as i32;
+        if from == 1 || to == 1 {
+            output.extend(std::iter::repeat_n(mono as i16, to));
+        } else {
+            output.extend((0..to).map(|c| if c < from { frame[c] } else { mono as i16 }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert<T: ToPcm16>(input: &[T]) -> Vec<i16> {
+        let mut output = vec![42];
+        convert_audio_chunk(input, &mut output);
+        output
+    }
+
+    #[test]
+    fn converts_f32() {
+        assert_eq!(
+            convert(&[0.0f32, 1.0, -1.0, 0.5, 2.0, -2.0]),
+            vec![0, i16::MAX, -i16::MAX, 16383, i16::MAX, -i16::MAX]
+        );
+    }
+
+    #[test]
+    fn converts_f64() {
+        assert_eq!(
+            convert(&[0.0f64, 1.0, -1.0, 3.0]),
+            vec![0, i16::MAX, -i16::MAX, i16::MAX]
+        );
+    }
+
+    #[test]
+    fn converts_i8() {
+        assert_eq!(
+            convert(&[0i8, 1, i8::MAX, i8::MIN]),
+            vec![0, 256, 32512, i16::MIN]
+        );
+    }
+
+    #[test]
+    fn converts_u8() {
+        assert_eq!(convert(&[128u8, 0, 255]), vec![0, i16::MIN, 32512]);
+    }
+
+    #[test]
+    fn converts_i16() {
+        assert_eq!(
+            convert(&[0i16, i16::MAX, i16::MIN, -5]),
+            vec![0, i16::MAX, i16::MIN, -5]
+        );
+    }
+
+    #[test]
+    fn converts_u16() {
+        assert_eq!(
+            convert(&[32768u16, 0, u16::MAX]),
+            vec![0, i16::MIN, i16::MAX]
+        );
+    }
+
+    #[test]
+    fn converts_i32() {
+        assert_eq!(
+            convert(&[0i32, i32::MAX, i32::MIN, 1 << 16]),
+            vec![0, i16::MAX, i16::MIN, 1]
+        );
+    }
+
+    #[test]
+    fn converts_u32() {
+        assert_eq!(
+            convert(&[1u32 << 31, 0, u32::MAX]),
+            vec![0, i16::MIN, i16::MAX]
+        );
+    }
 }
@@ -0,0 +1,111 @@
+use crate::types::audio::AudioSample;
+use std::collections::VecDeque;
+
+/// Energy-based voice activity gate.
+///
+/// A chunk opens the gate when its RMS level is above the threshold; the gate then
+/// stays open for the hangover period so word endings and short pauses still get sent.
+pub struct VoiceGate {
+    threshold: f32,
+    hangover: usize,
+    remaining: usize,
+    active: bool,
+}
+
+impl VoiceGate {
+    pub fn new(threshold_db: f32, hangover_ms: u32, sample_rate: u32, channels: u16) -> Self {
+        let threshold = 10f32.powf(threshold_db / 20.0) * i16::MAX as f32;
+        let hangover =
+            (sample_rate as u64 * hangover_ms as u64 / 1000) as usize * channels as usize;
+        Self {
+            threshold,
+            hangover,
+            remaining: 0,
+            active: false,
+        }
+    }
+
+    /// Returns `true` when the chunk should be uploaded.
+    pub fn process(&mut self, samples: &[i16]) -> bool {
+        if samples.is_empty() {
+            return self.active;
+        }
+
+        if rms(samples) >= self.threshold {
+            self.remaining = self.hangover;
+            self.active = true;
+        } else if self.remaining > 0 {
+            self.remaining = self.remaining.saturating_sub(samples.len());
+            self.active = true;
+        } else {
+            self.active = false;
+        }
+        self.active
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Hangover length in interleaved samples.
+    pub fn hangover(&self) -> usize {
+        self.hangover
+    }
+}
+
+/// The most recent gated chunks. The gate only opens once speech is loud enough,
+/// so they are sent ahead of the opening chunk to keep the start of the utterance.
+pub struct PreRoll {
+    chunks: VecDeque<AudioSample>,
+    len: usize,
+    capacity: usize,
+}
+
+impl PreRoll {
+    /// Keeps up to `capacity` interleaved samples.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            chunks: VecDeque::new(),
+            len: 0,
+            capacity,
+        }
+    }
+
+    /// Keeps `chunk` and returns the oldest one once they don't fit anymore, so
+    /// its buffer can be reused.
+    pub fn push(&mut self, chunk: AudioSample) -> Option<AudioSample> {
+        self.len += chunk.len();
+        self.chunks.push_back(chunk);
+        let mut evicted = None;
+        while self.len > self.capacity {
+            let Some(oldest) = self.chunks.pop_front() else {
+                break;
+            };
+            self.len -= oldest.len();
+            evicted = Some(oldest);
+        }
+        evicted
+    }
+
+    /// Hands out the kept chunks, oldest first.
+    pub fn drain(&mut self) -> impl Iterator<Item = AudioSample> + '_ {
+        self.len = 0;
+        self.chunks.drain(..)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+pub fn rms(samples: &[i16]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum: f64 = samples.iter().map(|&s| (s as f64) * (s as f64)).sum();
+    (sum / samples.len() as f64).sqrt() as f32
+}
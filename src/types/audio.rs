@@ -1 +1,30 @@
+use serde::{Deserialize, Serialize};
+
 pub type AudioSample = Vec<i16>;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioSource {
+    /// Loopback capture of what the selected output device plays.
+    #[default]
+    SystemOutput,
+    Microphone,
+    /// System audio and microphone mixed into one stream.
+    Mixed,
+}
+
+impl std::fmt::Display for AudioSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SystemOutput => write!(f, "System audio"),
+            Self::Microphone => write!(f, "Microphone"),
+            Self::Mixed => write!(f, "Both"),
+        }
+    }
+}
+
+impl AudioSource {
+    pub fn all() -> &'static [AudioSource] {
+        &[Self::SystemOutput, Self::Microphone, Self::Mixed]
+    }
+}
@@ -0,0 +1,70 @@
+use crate::types::languages::LanguageHint;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendKind {
+    #[default]
+    Soniox,
+}
+
+impl BackendKind {
+    pub fn all() -> &'static [BackendKind] {
+        &[Self::Soniox]
+    }
+}
+
+impl std::fmt::Display for BackendKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Which stream of a translated session a token belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TranslationStatus {
+    /// Plain transcription, translation is off.
+    #[default]
+    Untranslated,
+    /// Spoken text that a translation will follow.
+    Original,
+    Translation,
+}
+
+/// One piece of recognized text, the same for every backend.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TranscriptToken {
+    pub text: String,
+    /// Relative to the session, or to the start of capture once the worker mapped it
+    /// with an [`crate::transcription::clock::AudioClock`].
+    pub start_ms: Option<f64>,
+    pub end_ms: Option<f64>,
+    pub confidence: f64,
+    pub is_final: bool,
+    /// Ends the current utterance. Such a token carries no text.
+    pub endpoint: bool,
+    pub speaker: Option<String>,
+    pub language: Option<LanguageHint>,
+    pub source_language: Option<LanguageHint>,
+    pub translation: TranslationStatus,
+}
+
+impl TranscriptToken {
+    pub fn is_endpoint(&self) -> bool {
+        self.endpoint
+    }
+
+    pub fn is_original(&self) -> bool {
+        self.translation == TranslationStatus::Original
+    }
+
+    pub fn is_translation(&self) -> bool {
+        self.translation == TranslationStatus::Translation
+    }
+}
+
+/// Tokens a backend reported in one message.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TranscriptUpdate {
+    pub tokens: Vec<TranscriptToken>,
+}
@@ -1,12 +1,15 @@
 use crate::errors::SonioxLiveErrors;
-use crate::types::soniox::SonioxTranscriptionResponse;
+use crate::types::backend::TranscriptUpdate;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub enum SonioxEvent {
-    Transcription(SonioxTranscriptionResponse),
+    Transcription(TranscriptUpdate),
     Warning(String),
     Error(SonioxLiveErrors),
     Connected(bool),
+    Reconnecting { attempt: u32, delay: Duration },
+    VoiceActivity(bool),
 }
 
 impl From<&str> for SonioxEvent {
@@ -14,3 +17,19 @@ impl From<&str> for SonioxEvent {
         Self::Warning(value.into())
     }
 }
+
+/// Normalized message produced by a [`crate::transcription::backend::BackendSession`].
+#[derive(Debug)]
+pub enum BackendEvent {
+    Transcription(TranscriptUpdate),
+    Reconnect,
+    Fatal(SonioxLiveErrors),
+    Ignored,
+}
+
+/// Sent from the app to the running worker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkerCommand {
+    /// Turn the current interim text into final text without waiting for a pause.
+    Finalize,
+}
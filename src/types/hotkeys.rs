@@ -0,0 +1,233 @@
+use eframe::egui::{Key, Modifiers};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HotkeyAction {
+    /// Hide the subtitles without stopping the session, or go back to the overlay from
+    /// the settings of a running session.
+    ToggleOverlay,
+    /// Stop and resume audio capture.
+    PauseCapture,
+    /// Leave the overlay and go back to the settings window.
+    OpenSettings,
+    /// Drop every block currently on screen.
+    ClearSubtitles,
+    /// Finalize the interim text right away.
+    Finalize,
+}
+
+impl std::fmt::Display for HotkeyAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ToggleOverlay => write!(f, "Show / hide overlay"),
+            Self::PauseCapture => write!(f, "Pause / resume audio"),
+            Self::OpenSettings => write!(f, "Open settings"),
+            Self::ClearSubtitles => write!(f, "Clear subtitles"),
+            Self::Finalize => write!(f, "Finalize now"),
+        }
+    }
+}
+
+impl HotkeyAction {
+    pub fn all() -> &'static [HotkeyAction] {
+        &[
+            Self::ToggleOverlay,
+            Self::PauseCapture,
+            Self::OpenSettings,
+            Self::ClearSubtitles,
+            Self::Finalize,
+        ]
+    }
+}
+
+/// A key with modifiers, stored as text like `Ctrl+Shift+H`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyBinding {
+    pub key: Key,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyBinding {
+    pub const fn new(key: Key) -> Self {
+        Self {
+            key,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    pub const fn ctrl_shift(key: Key) -> Self {
+        Self {
+            key,
+            ctrl: true,
+            shift: true,
+            alt: false,
+        }
+    }
+
+    pub fn from_input(key: Key, modifiers: Modifiers) -> Self {
+        Self {
+            key,
+            ctrl: modifiers.command || modifiers.ctrl,
+            shift: modifiers.shift,
+            alt: modifiers.alt,
+        }
+    }
+
+    pub fn modifiers(&self) -> Modifiers {
+        Modifiers {
+            alt: self.alt,
+            ctrl: self.ctrl,
+            shift: self.shift,
+            mac_cmd: false,
+            command: self.ctrl,
+        }
+    }
+}
+
+impl std::fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        write!(f, "{}", self.key.name())
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let name = parts.pop().filter(|p| !p.is_empty());
+        let key = name
+            .and_then(Key::from_name)
+            .ok_or_else(|| format!("unknown key in hotkey `{}`", s))?;
+        let mut binding = Self::new(key);
+        for modifier in parts {
+            match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" | "cmd" => binding.ctrl = true,
+                "shift" => binding.shift = true,
+                "alt" => binding.alt = true,
+                other => return Err(format!("unknown modifier `{}` in hotkey `{}`", other, s)),
+            }
+        }
+        Ok(binding)
+    }
+}
+
+impl TryFrom<String> for KeyBinding {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<KeyBinding> for String {
+    fn from(value: KeyBinding) -> Self {
+        value.to_string()
+    }
+}
+
+/// Hotkey per action. An unset binding disables the action and is saved as `""`,
+/// so that it doesn't fall back to the default on the next load.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HotkeyBindings {
+    #[serde(with = "optional_binding")]
+    pub toggle_overlay: Option<KeyBinding>,
+    #[serde(with = "optional_binding")]
+    pub pause_capture: Option<KeyBinding>,
+    #[serde(with = "optional_binding")]
+    pub open_settings: Option<KeyBinding>,
+    #[serde(with = "optional_binding")]
+    pub clear_subtitles: Option<KeyBinding>,
+    #[serde(with = "optional_binding")]
+    pub finalize: Option<KeyBinding>,
+}
+
+mod optional_binding {
+    use super::KeyBinding;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        binding: &Option<KeyBinding>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match binding {
+            Some(binding) => serializer.collect_str(binding),
+            None => serializer.serialize_str(""),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<KeyBinding>, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        if value.trim().is_empty() {
+            return Ok(None);
+        }
+        value.parse().map(Some).map_err(serde::de::Error::custom)
+    }
+}
+
+/// The hotkeys are global, so the defaults stay off letters and plain function keys
+/// that other applications use for saving, copying or debugging.
+impl Default for HotkeyBindings {
+    fn default() -> Self {
+        Self {
+            toggle_overlay: Some(KeyBinding::ctrl_shift(Key::F9)),
+            pause_capture: Some(KeyBinding::ctrl_shift(Key::F10)),
+            open_settings: Some(KeyBinding::ctrl_shift(Key::F11)),
+            clear_subtitles: Some(KeyBinding::ctrl_shift(Key::F12)),
+            finalize: Some(KeyBinding::ctrl_shift(Key::F8)),
+        }
+    }
+}
+
+impl HotkeyBindings {
+    pub fn get(&self, action: HotkeyAction) -> Option<KeyBinding> {
+        match action {
+            HotkeyAction::ToggleOverlay => self.toggle_overlay,
+            HotkeyAction::PauseCapture => self.pause_capture,
+            HotkeyAction::OpenSettings => self.open_settings,
+            HotkeyAction::ClearSubtitles => self.clear_subtitles,
+            HotkeyAction::Finalize => self.finalize,
+        }
+    }
+
+    pub fn get_mut(&mut self, action: HotkeyAction) -> &mut Option<KeyBinding> {
+        match action {
+            HotkeyAction::ToggleOverlay => &mut self.toggle_overlay,
+            HotkeyAction::PauseCapture => &mut self.pause_capture,
+            HotkeyAction::OpenSettings => &mut self.open_settings,
+            HotkeyAction::ClearSubtitles => &mut self.clear_subtitles,
+            HotkeyAction::Finalize => &mut self.finalize,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (HotkeyAction, KeyBinding)> + '_ {
+        HotkeyAction::all()
+            .iter()
+            .filter_map(|&action| self.get(action).map(|binding| (action, binding)))
+    }
+
+    /// First action bound to the same keys as `binding`, other than `action` itself.
+    pub fn conflict(&self, action: HotkeyAction, binding: KeyBinding) -> Option<HotkeyAction> {
+        self.iter()
+            .find(|&(other, b)| other != action && b == binding)
+            .map(|(other, _)| other)
+    }
+}
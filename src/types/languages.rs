@@ -189,6 +189,72 @@ impl LanguageHint {
             Self::Welsh,
         ]
     }
+
+    /// ISO 639-1 code, as used by the API.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Afrikaans => "af",
+            Self::Albanian => "sq",
+            Self::Arabic => "ar",
+            Self::Azerbaijani => "az",
+            Self::Basque => "eu",
+            Self::Belarusian => "be",
+            Self::Bengali => "bn",
+            Self::Bosnian => "bs",
+            Self::Bulgarian => "bg",
+            Self::Catalan => "ca",
+            Self::Chinese => "zh",
+            Self::Croatian => "hr",
+            Self::Czech => "cs",
+            Self::Danish => "da",
+            Self::Dutch => "nl",
+            Self::English => "en",
+            Self::Estonian => "et",
+            Self::Finnish => "fi",
+            Self::French => "fr",
+            Self::Galician => "gl",
+            Self::German => "de",
+            Self::Greek => "el",
+            Self::Gujarati => "gu",
+            Self::Hebrew => "he",
+            Self::Hindi => "hi",
+            Self::Hungarian => "hu",
+            Self::Indonesian => "id",
+            Self::Italian => "it",
+            Self::Japanese => "ja",
+            Self::Kannada => "kn",
+            Self::Kazakh => "kk",
+            Self::Korean => "ko",
+            Self::Latvian => "lv",
+            Self::Lithuanian => "lt",
+            Self::Macedonian => "mk",
+            Self::Malay => "ms",
+            Self::Malayalam => "ml",
+            Self::Marathi => "mr",
+            Self::Norwegian => "no",
+            Self::Persian => "fa",
+            Self::Polish => "pl",
+            Self::Portuguese => "pt",
+            Self::Punjabi => "pa",
+            Self::Romanian => "ro",
+            Self::Russian => "ru",
+            Self::Serbian => "sr",
+            Self::Slovak => "sk",
+            Self::Slovenian => "sl",
+            Self::Spanish => "es",
+            Self::Swahili => "sw",
+            Self::Swedish => "sv",
+            Self::Tagalog => "tl",
+            Self::Tamil => "ta",
+            Self::Telugu => "te",
+            Self::Thai => "th",
+            Self::Turkish => "tr",
+            Self::Ukrainian => "uk",
+            Self::Urdu => "ur",
+            Self::Vietnamese => "vi",
+            Self::Welsh => "cy",
+        }
+    }
 }
 
 impl Default for LanguageHint {
@@ -1,5 +1,11 @@
 pub mod audio;
+pub mod backend;
 pub mod events;
+pub mod hotkeys;
 pub mod languages;
+pub mod overlay;
 pub mod soniox;
+pub mod speakers;
 pub mod subtitles;
+pub mod transcript;
+pub mod translation;
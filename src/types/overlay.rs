@@ -0,0 +1,210 @@
+use eframe::egui::{Align, Align2, Pos2, Rect, Vec2, vec2};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Screen point the subtitle box is attached to.
+///
+/// Older configs stored it as a number from 0 (top left) to 8 (bottom right),
+/// row by row; those still load.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case", try_from = "AnchorRepr")]
+pub enum OverlayAnchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    #[default]
+    Bottom,
+    BottomRight,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AnchorRepr {
+    Index(i64),
+    Name(String),
+}
+
+impl TryFrom<AnchorRepr> for OverlayAnchor {
+    type Error = String;
+
+    fn try_from(value: AnchorRepr) -> Result<Self, Self::Error> {
+        match value {
+            AnchorRepr::Index(index) => Ok(Self::from_index(index)),
+            AnchorRepr::Name(name) => name.parse(),
+        }
+    }
+}
+
+impl FromStr for OverlayAnchor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|anchor| anchor.as_str() == s)
+            .ok_or_else(|| format!("unknown anchor `{}`", s))
+    }
+}
+
+impl std::fmt::Display for OverlayAnchor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TopLeft => write!(f, "Top left"),
+            Self::Top => write!(f, "Top"),
+            Self::TopRight => write!(f, "Top right"),
+            Self::Left => write!(f, "Left"),
+            Self::Center => write!(f, "Center"),
+            Self::Right => write!(f, "Right"),
+            Self::BottomLeft => write!(f, "Bottom left"),
+            Self::Bottom => write!(f, "Bottom"),
+            Self::BottomRight => write!(f, "Bottom right"),
+        }
+    }
+}
+
+impl OverlayAnchor {
+    /// Row by row, the same order the old numeric values used.
+    pub fn all() -> &'static [OverlayAnchor] {
+        &[
+            Self::TopLeft,
+            Self::Top,
+            Self::TopRight,
+            Self::Left,
+            Self::Center,
+            Self::Right,
+            Self::BottomLeft,
+            Self::Bottom,
+            Self::BottomRight,
+        ]
+    }
+
+    /// Numbers past 8 used to fall back to the bottom center silently, now it's logged.
+    pub fn from_index(index: i64) -> Self {
+        usize::try_from(index)
+            .ok()
+            .and_then(|i| Self::all().get(i).copied())
+            .unwrap_or_else(|| {
+                tracing::warn!("anchor {} is out of range, using the bottom center", index);
+                Self::default()
+            })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::TopLeft => "top_left",
+            Self::Top => "top",
+            Self::TopRight => "top_right",
+            Self::Left => "left",
+            Self::Center => "center",
+            Self::Right => "right",
+            Self::BottomLeft => "bottom_left",
+            Self::Bottom => "bottom",
+            Self::BottomRight => "bottom_right",
+        }
+    }
+
+    pub fn align(&self) -> Align2 {
+        match self {
+            Self::TopLeft => Align2::LEFT_TOP,
+            Self::Top => Align2::CENTER_TOP,
+            Self::TopRight => Align2::RIGHT_TOP,
+            Self::Left => Align2::LEFT_CENTER,
+            Self::Center => Align2::CENTER_CENTER,
+            Self::Right => Align2::RIGHT_CENTER,
+            Self::BottomLeft => Align2::LEFT_BOTTOM,
+            Self::Bottom => Align2::CENTER_BOTTOM,
+            Self::BottomRight => Align2::RIGHT_BOTTOM,
+        }
+    }
+
+    pub fn from_align(align: Align2) -> Self {
+        let index = |align: Align| match align {
+            Align::Min => 0,
+            Align::Center => 1,
+            Align::Max => 2,
+        };
+        Self::all()[index(align.y()) * 3 + index(align.x())]
+    }
+
+    pub fn arrow(&self) -> &'static str {
+        match self {
+            Self::TopLeft => "↖",
+            Self::Top => "⬆",
+            Self::TopRight => "↗",
+            Self::Left => "←",
+            Self::Center => "X",
+            Self::Right => "→",
+            Self::BottomLeft => "↙",
+            Self::Bottom => "⬇",
+            Self::BottomRight => "↘",
+        }
+    }
+
+    /// Offset that keeps the box `pad` points away from the edges it is attached to.
+    pub fn default_offset(&self, pad: f32) -> Vec2 {
+        let step = |align: Align| match align {
+            Align::Min => pad,
+            Align::Center => 0.0,
+            Align::Max => -pad,
+        };
+        let align = self.align();
+        vec2(step(align.x()), step(align.y()))
+    }
+}
+
+/// A display the overlay can open on: its system name and its bounds in desktop pixels.
+///
+/// Displays are matched by name. The saved bounds only find a display again whose name
+/// changed, the window always goes to the bounds the display has now.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct OverlayMonitor {
+    /// Device name of the display, e.g. `\\.\DISPLAY2` on Windows.
+    pub name: String,
+    pub position: (f32, f32),
+    pub size: (f32, f32),
+}
+
+impl OverlayMonitor {
+    pub fn rect(&self) -> Rect {
+        Rect::from_min_size(
+            Pos2::new(self.position.0, self.position.1),
+            vec2(self.size.0, self.size.1),
+        )
+    }
+
+    /// This display among the attached ones, or `None` once it's gone.
+    pub fn locate<'a>(&self, attached: &'a [OverlayMonitor]) -> Option<&'a OverlayMonitor> {
+        attached
+            .iter()
+            .find(|monitor| monitor.name == self.name)
+            .or_else(|| {
+                attached
+                    .iter()
+                    .find(|monitor| monitor.rect() == self.rect())
+            })
+    }
+
+    /// The name without the `\\.\` device prefix Windows puts in front of it.
+    pub fn label(&self) -> &str {
+        self.name.trim_start_matches(r"\\.\")
+    }
+}
+
+impl std::fmt::Display for OverlayMonitor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {:.0} × {:.0} at ({:.0}, {:.0})",
+            self.label(),
+            self.size.0,
+            self.size.1,
+            self.position.0,
+            self.position.1
+        )
+    }
+}
@@ -14,7 +14,7 @@ pub struct SonioxTranslationObject {
 #[serde(default)]
 pub struct SonioxTranscriptionRequest {
     pub api_key: Arc<str>,
-    pub model: &'static str,
+    pub model: Arc<str>,
     pub audio_format: &'static str,
     pub num_channels: Option<u32>,           // required for raw audio
     pub sample_rate: Option<u32>,            // required for raw audio
@@ -42,6 +42,18 @@ pub struct SonioxTranscriptionToken {
     pub translation_status: Option<String>, // maybe add enum?
 }
 
+impl SonioxTranscriptionToken {
+    /// Marker sent with endpoint detection once the speaker finished an utterance.
+    pub const ENDPOINT: &'static str = "<end>";
+    /// Marker sent once a requested finalization is done.
+    pub const FINALIZED: &'static str = "<fin>";
+
+    /// Both markers end the current utterance and carry no text of their own.
+    pub fn is_endpoint(&self) -> bool {
+        self.text == Self::ENDPOINT || self.text == Self::FINALIZED
+    }
+}
+
 #[derive(Debug, Deserialize, Default)]
 pub struct SonioxTranscriptionResponse {
     pub tokens: Vec<SonioxTranscriptionToken>,
@@ -62,3 +74,13 @@ pub enum SonioxTranscriptionMessage {
     Error(SonioxTranscriptionError),
     Response(SonioxTranscriptionResponse),
 }
+
+/// Text messages a client may send after the config, between audio frames.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SonioxControlMessage {
+    /// Asks the server to finalize every pending token right away.
+    Finalize,
+    /// Keeps the session open while no audio is sent.
+    Keepalive,
+}
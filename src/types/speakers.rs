@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const PALETTE: [(u8, u8, u8); 8] = [
+    (102, 204, 255),
+    (255, 153, 102),
+    (153, 230, 153),
+    (255, 128, 191),
+    (204, 170, 255),
+    (255, 230, 128),
+    (128, 230, 217),
+    (230, 179, 128),
+];
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct SpeakerProfile {
+    pub name: String,
+    pub color: (u8, u8, u8),
+}
+
+impl Default for SpeakerProfile {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            color: PALETTE[0],
+        }
+    }
+}
+
+/// Names and colors for the speaker ids reported by diarization.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct SpeakerRegistry {
+    speakers: BTreeMap<String, SpeakerProfile>,
+}
+
+impl SpeakerRegistry {
+    pub fn get(&self, id: &str) -> Option<&SpeakerProfile> {
+        self.speakers.get(id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &SpeakerProfile)> {
+        self.speakers.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&String, &mut SpeakerProfile)> {
+        self.speakers.iter_mut()
+    }
+
+    pub fn len(&self) -> usize {
+        self.speakers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.speakers.is_empty()
+    }
+
+    /// Adds an unknown id with the next palette color. Returns whether it was new.
+    pub fn register(&mut self, id: &str) -> bool {
+        if self.speakers.contains_key(id) {
+            return false;
+        }
+        let color = PALETTE[self.speakers.len() % PALETTE.len()];
+        self.speakers.insert(
+            id.to_string(),
+            SpeakerProfile {
+                name: String::new(),
+                color,
+            },
+        );
+        true
+    }
+
+    pub fn remove(&mut self, id: &str) {
+        self.speakers.remove(id);
+    }
+
+    pub fn clear(&mut self) {
+        self.speakers.clear();
+    }
+
+    /// The user-defined name, falling back to the raw id.
+    pub fn label<'a>(&'a self, id: &'a str) -> &'a str {
+        match self.speakers.get(id) {
+            Some(profile) if !profile.name.trim().is_empty() => profile.name.trim(),
+            _ => id,
+        }
+    }
+}
@@ -1,7 +1,27 @@
-#[derive(Debug, Default, PartialEq, Eq, Clone)]
+use crate::types::backend::TranscriptToken;
+use crate::types::languages::LanguageHint;
+use serde::{Deserialize, Serialize};
+
+/// Byte range of one token inside [`SubtitleBlock::text`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TokenSpan {
+    pub start: usize,
+    pub end: usize,
+    pub confidence: f32,
+}
+
+#[derive(Debug, Default, PartialEq, Clone)]
 pub struct SubtitleBlock {
     pub(crate) speaker: Option<String>,
     pub(crate) text: String,
+    /// Source text of a translated block, kept only for the dual-line display.
+    pub(crate) original: String,
+    pub(crate) language: Option<LanguageHint>,
+    pub(crate) source_language: Option<LanguageHint>,
+    pub(crate) spans: Vec<TokenSpan>,
+    /// Set by an endpoint, the next spoken token starts a new block while its
+    /// translation may still join this one.
+    pub(crate) closed: bool,
 }
 
 impl SubtitleBlock {
@@ -11,4 +31,129 @@ impl SubtitleBlock {
             ..Default::default()
         }
     }
+
+    pub(crate) fn push(&mut self, token: &TranscriptToken) {
+        if token.is_original() {
+            self.original.push_str(&token.text);
+            self.source_language = self.source_language.or(token.language);
+            return;
+        }
+        let start = self.text.len();
+        self.text.push_str(&token.text);
+        self.spans.push(TokenSpan {
+            start,
+            end: self.text.len(),
+            confidence: token.confidence as f32,
+        });
+        self.language = self.language.or(token.language);
+        self.source_language = self.source_language.or(token.source_language);
+    }
+
+    pub fn speaker(&self) -> Option<&str> {
+        self.speaker.as_deref()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn spans(&self) -> &[TokenSpan] {
+        &self.spans
+    }
+
+    pub fn original(&self) -> &str {
+        &self.original
+    }
+
+    pub fn language(&self) -> Option<LanguageHint> {
+        self.language
+    }
+
+    /// Language that was actually spoken, which for translated text is the source.
+    pub fn spoken_language(&self) -> Option<LanguageHint> {
+        self.source_language.or(self.language)
+    }
+
+    /// Source and target language of a translated block.
+    pub fn direction(&self) -> Option<(LanguageHint, LanguageHint)> {
+        let source = self.source_language?;
+        let target = self.language?;
+        (source != target).then_some((source, target))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty() && self.original.is_empty()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfidenceMode {
+    #[default]
+    Off,
+    Dim,
+    Underline,
+}
+
+impl std::fmt::Display for ConfidenceMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Off => write!(f, "Off"),
+            Self::Dim => write!(f, "Dim"),
+            Self::Underline => write!(f, "Underline"),
+        }
+    }
+}
+
+impl ConfidenceMode {
+    pub fn all() -> &'static [ConfidenceMode] {
+        &[Self::Off, Self::Dim, Self::Underline]
+    }
+}
+
+/// How tokens are rendered depending on the confidence reported by the API.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfidenceStyle {
+    pub mode: ConfidenceMode,
+    /// Tokens below this are dimmed or underlined.
+    pub threshold: f32,
+    /// Tokens below this aren't drawn at all.
+    pub floor: f32,
+}
+
+impl ConfidenceStyle {
+    pub fn is_hidden(&self, confidence: f32) -> bool {
+        confidence < self.floor
+    }
+
+    pub fn is_marked(&self, confidence: f32) -> bool {
+        self.mode != ConfidenceMode::Off && confidence < self.threshold
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LanguageDisplay {
+    Off,
+    /// A short tag like `[ES]` in front of each replica.
+    #[default]
+    Tag,
+    /// Each language gets its own text color.
+    Color,
+}
+
+impl std::fmt::Display for LanguageDisplay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Off => write!(f, "Off"),
+            Self::Tag => write!(f, "Tag"),
+            Self::Color => write!(f, "Color"),
+        }
+    }
+}
+
+impl LanguageDisplay {
+    pub fn all() -> &'static [LanguageDisplay] {
+        &[Self::Off, Self::Tag, Self::Color]
+    }
 }
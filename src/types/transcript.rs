@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptLogFormat {
+    /// One JSON object per utterance.
+    #[default]
+    JsonLines,
+    PlainText,
+}
+
+impl std::fmt::Display for TranscriptLogFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::JsonLines => write!(f, "JSON Lines"),
+            Self::PlainText => write!(f, "Plain text"),
+        }
+    }
+}
+
+impl TranscriptLogFormat {
+    pub fn all() -> &'static [TranscriptLogFormat] {
+        &[Self::JsonLines, Self::PlainText]
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::JsonLines => "jsonl",
+            Self::PlainText => "txt",
+        }
+    }
+}
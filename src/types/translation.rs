@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TranslationMode {
+    /// Everything is translated into the target language.
+    #[default]
+    OneWay,
+    /// Speech in either language is translated into the other one.
+    TwoWay,
+}
+
+impl std::fmt::Display for TranslationMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OneWay => write!(f, "One-way"),
+            Self::TwoWay => write!(f, "Two-way"),
+        }
+    }
+}
+
+impl TranslationMode {
+    pub fn all() -> &'static [TranslationMode] {
+        &[Self::OneWay, Self::TwoWay]
+    }
+
+    pub fn as_request_type(&self) -> &'static str {
+        match self {
+            Self::OneWay => "one_way",
+            Self::TwoWay => "two_way",
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use soniox_live::transcription::mixer::AudioMixer;
+use soniox_live::transcription::utils::{AudioConverter, remix};
+
+#[test]
+fn mixer_waits_for_both_sources() {
+    let mut mixer = AudioMixer::new([1.0, 1.0], 100);
+    let mut output = Vec::new();
+
+    mixer.push(0, &[100, 200, 300]);
+    mixer.drain(&mut output);
+    assert!(output.is_empty());
+
+    mixer.push(1, &[10, 20]);
+    mixer.drain(&mut output);
+    assert_eq!(output, vec![110, 220]);
+}
+
+#[test]
+fn mixer_applies_gain_and_saturates() {
+    let mut mixer = AudioMixer::new([0.5, 2.0], 100);
+    let mut output = Vec::new();
+
+    mixer.push(0, &[1000, i16::MAX]);
+    mixer.push(1, &[100, i16::MAX]);
+    mixer.drain(&mut output);
+    assert_eq!(output, vec![700, i16::MAX]);
+}
+
+#[test]
+fn mixer_flushes_idle_source_as_silence() {
+    let mut mixer = AudioMixer::new([1.0, 1.0], 4);
+    let mut output = Vec::new();
+
+    mixer.push(1, &[1, 2, 3, 4, 5, 6]);
+    mixer.drain(&mut output);
+    assert_eq!(output, vec![1, 2]);
+
+    mixer.push(0, &[10, 10, 10, 10]);
+    mixer.drain(&mut output);
+    assert_eq!(output, vec![13, 14, 15, 16]);
+}
+
+#[test]
+fn remix_downmixes_to_mono() {
+    let mut output = Vec::new();
+    remix(&[100, 300, -50, 50], 2, 1, &mut output);
+    assert_eq!(output, vec![200, 0]);
+}
+
+#[test]
+fn remix_duplicates_mono() {
+    let mut output = Vec::new();
+    remix(&[1, 2], 1, 2, &mut output);
+    assert_eq!(output, vec![1, 1, 2, 2]);
+}
+
+#[test]
+fn converter_passes_matching_format_through() {
+    let mut converter = AudioConverter::new(16000, 1, 16000, 1);
+    let mut output = Vec::new();
+    converter.process(&[1, 2, 3], &mut output);
+    assert!(converter.is_passthrough());
+    assert_eq!(output, vec![1, 2, 3]);
+}
+
+#[test]
+fn converter_downsamples_across_chunks() {
+    let mut converter = AudioConverter::new(48000, 2, 16000, 1);
+    let mut output = Vec::new();
+    let mut total = 0;
+    for _ in 0..10 {
+        let chunk: Vec<i16> = (0..960).map(|i| (i % 2) as i16 * 200).collect();
+        converter.process(&chunk, &mut output);
+        assert!(output.iter().all(|&s| s == 100));
+        total += output.len();
+    }
+    assert!((1598..=1602).contains(&total), "got {} samples", total);
+}
+
+fn tone_rms(frequency: f64) -> f64 {
+    let mut converter = AudioConverter::new(48000, 1, 16000, 1);
+    let mut output = Vec::new();
+    let mut all = Vec::new();
+    for chunk in 0..20 {
+        let input: Vec<i16> = (0..480)
+            .map(|i| {
+                let t = (chunk * 480 + i) as f64 / 48000.0;
+                (10000.0 * (2.0 * std::f64::consts::PI * frequency * t).sin()) as i16
+            })
+            .collect();
+        converter.process(&input, &mut output);
+        all.extend_from_slice(&output);
+    }
+    // Skips the start, where the filter still reaches back to the first sample.
+    let settled = &all[100..];
+    let power = settled.iter().map(|&s| (s as f64).powi(2)).sum::<f64>() / settled.len() as f64;
+    power.sqrt()
+}
+
+#[test]
+fn converter_filters_tones_above_output_nyquist() {
+    let speech = tone_rms(1000.0);
+    // 12 kHz would fold back to 4 kHz at a 16 kHz output rate.
+    let aliased = tone_rms(12000.0);
+    assert!(speech > 6500.0, "1 kHz came out at {}", speech);
+    assert!(aliased < speech * 0.01, "12 kHz came out at {}", aliased);
+}
+
+#[test]
+fn converter_interpolates_when_upsampling() {
+    let mut converter = AudioConverter::new(8000, 1, 16000, 1);
+    let mut output = Vec::new();
+    let mut all = Vec::new();
+    for chunk in [[0, 100], [200, 300]] {
+        converter.process(&chunk, &mut output);
+        all.extend_from_slice(&output);
+    }
+    assert_eq!(all, vec![0, 50, 100, 150, 200, 250]);
+}
+
+#[test]
+fn converter_upmixes_to_stereo_target() {
+    let mut converter = AudioConverter::new(16000, 1, 16000, 2);
+    let mut output = Vec::new();
+    converter.process(&[5, -5], &mut output);
+    assert!(!converter.is_passthrough());
+    assert_eq!(output, vec![5, 5, -5, -5]);
+}
@@ -0,0 +1,50 @@
+use soniox_live::soniox::backlog::AudioBacklog;
+
+#[test]
+fn keeps_samples_in_order() {
+    let mut backlog = AudioBacklog::new(8);
+    backlog.push(&[1, 2, 3]);
+    backlog.push(&[4, 5]);
+
+    assert_eq!(backlog.len(), 5);
+    assert_eq!(backlog.pop_chunk(), Some(vec![1, 2, 3, 4, 5]));
+    assert_eq!(backlog.pop_chunk(), None);
+    assert!(backlog.is_empty());
+}
+
+#[test]
+fn drops_oldest_when_full() {
+    let mut backlog = AudioBacklog::new(4);
+    backlog.push(&[1, 2, 3]);
+    backlog.push(&[4, 5, 6]);
+    assert_eq!(backlog.pop_chunk(), Some(vec![3, 4, 5, 6]));
+
+    backlog.push(&[7, 8, 9, 10, 11, 12]);
+    assert_eq!(backlog.pop_chunk(), Some(vec![9, 10, 11, 12]));
+}
+
+#[test]
+fn zero_capacity_disables_buffering() {
+    let mut backlog = AudioBacklog::new(0);
+    backlog.push(&[1, 2, 3]);
+    assert!(backlog.is_empty());
+}
+
+#[test]
+fn capacity_follows_duration_and_format() {
+    let backlog = AudioBacklog::with_duration(2.5, 16000, 2);
+    assert_eq!(backlog.capacity(), 80_000);
+}
+
+#[test]
+fn replays_in_bounded_chunks() {
+    let mut backlog = AudioBacklog::new(10_000);
+    backlog.push(&vec![0; 10_000]);
+
+    let mut total = 0;
+    while let Some(chunk) = backlog.pop_chunk() {
+        assert!(chunk.len() <= 4096);
+        total += chunk.len();
+    }
+    assert_eq!(total, 10_000);
+}
@@ -0,0 +1,230 @@
+#![allow(dead_code)]
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{Value, json};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::accept_async;
+use tungstenite::Message;
+
+/// One scripted action the fake server performs on a connection.
+#[derive(Clone, Debug)]
+pub enum Step {
+    /// Drops the TCP connection before the WebSocket handshake. Only valid as the first step.
+    Reject,
+    /// Waits until at least this many bytes of PCM arrived on the connection.
+    WaitAudio(usize),
+    /// Waits until the client ended the audio with an empty frame, like Soniox does
+    /// before it sends the last results and closes.
+    WaitEnd,
+    Tokens(Value),
+    Error(usize, &'static str),
+    Raw(&'static str),
+    Close,
+    Sleep(Duration),
+}
+
+#[derive(Default, Debug)]
+pub struct Recorded {
+    pub configs: Vec<Value>,
+    pub audio_bytes: Vec<usize>,
+    /// Text messages sent after the config, e.g. finalize or keepalive.
+    pub controls: Vec<Value>,
+    /// Connections on which the client ended the audio with an empty frame.
+    pub ended: Vec<usize>,
+}
+
+/// Fake Soniox WebSocket server. Connection `n` replays `scripts[n]`,
+/// connections beyond the script list are accepted and left idle.
+pub struct MockServer {
+    pub addr: SocketAddr,
+    pub recorded: Arc<Mutex<Recorded>>,
+    handle: JoinHandle<()>,
+}
+
+impl MockServer {
+    pub async fn start(scripts: Vec<Vec<Step>>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let recorded = Arc::new(Mutex::new(Recorded::default()));
+
+        let rec = recorded.clone();
+        let handle = tokio::spawn(async move {
+            let mut index = 0;
+            while let Ok((stream, _)) = listener.accept().await {
+                let script = scripts.get(index).cloned().unwrap_or_default();
+                let rec = rec.clone();
+                index += 1;
+                if matches!(script.first(), Some(Step::Reject)) {
+                    drop(stream);
+                    continue;
+                }
+                tokio::spawn(async move {
+                    if let Ok(ws) = accept_async(stream).await {
+                        serve(ws, script, rec).await;
+                    }
+                });
+            }
+        });
+
+        Self {
+            addr,
+            recorded,
+            handle,
+        }
+    }
+
+    pub fn url(&self) -> String {
+        format!("ws://{}", self.addr)
+    }
+
+    pub fn connections(&self) -> usize {
+        self.recorded.lock().unwrap().configs.len()
+    }
+
+    pub async fn wait_audio(&self, connection: usize, bytes: usize) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let received = {
+                    let rec = self.recorded.lock().unwrap();
+                    rec.audio_bytes.get(connection).copied().unwrap_or(0)
+                };
+                if received >= bytes {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("timed out waiting for audio");
+    }
+
+    pub async fn wait_control(&self, kind: &str) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let found = {
+                    let rec = self.recorded.lock().unwrap();
+                    rec.controls.iter().any(|c| c["type"] == kind)
+                };
+                if found {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("timed out waiting for control message");
+    }
+
+    pub async fn wait_connections(&self, count: usize) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while self.connections() < count {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("timed out waiting for connections");
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn serve(
+    ws: tokio_tungstenite::WebSocketStream<tokio::net::TcpStream>,
+    script: Vec<Step>,
+    recorded: Arc<Mutex<Recorded>>,
+) {
+    let (mut tx, mut rx) = ws.split();
+    let Some(Ok(Message::Text(config))) = rx.next().await else {
+        return;
+    };
+    let slot = {
+        let mut rec = recorded.lock().unwrap();
+        rec.configs
+            .push(serde_json::from_str(&config).unwrap_or(Value::Null));
+        rec.audio_bytes.push(0);
+        rec.audio_bytes.len() - 1
+    };
+
+    let record_audio = |msg: &Message| match msg {
+        Message::Binary(data) if data.is_empty() => recorded.lock().unwrap().ended.push(slot),
+        Message::Binary(data) => recorded.lock().unwrap().audio_bytes[slot] += data.len(),
+        Message::Text(text) => recorded
+            .lock()
+            .unwrap()
+            .controls
+            .push(serde_json::from_str(text).unwrap_or(Value::Null)),
+        _ => {}
+    };
+
+    for step in script {
+        match step {
+            Step::WaitAudio(bytes) => {
+                while recorded.lock().unwrap().audio_bytes[slot] < bytes {
+                    match rx.next().await {
+                        Some(Ok(msg)) => record_audio(&msg),
+                        _ => return,
+                    }
+                }
+            }
+            Step::WaitEnd => {
+                while !recorded.lock().unwrap().ended.contains(&slot) {
+                    match rx.next().await {
+                        Some(Ok(msg)) => record_audio(&msg),
+                        _ => return,
+                    }
+                }
+            }
+            Step::Tokens(tokens) => {
+                let body = json!({
+                    "tokens": tokens,
+                    "final_audio_proc_ms": 0.0,
+                    "total_audio_proc_ms": 0.0,
+                });
+                if tx.send(Message::text(body.to_string())).await.is_err() {
+                    return;
+                }
+            }
+            Step::Error(code, message) => {
+                let body = json!({ "error_code": code, "error_message": message });
+                if tx.send(Message::text(body.to_string())).await.is_err() {
+                    return;
+                }
+            }
+            Step::Raw(text) => {
+                if tx.send(Message::text(text)).await.is_err() {
+                    return;
+                }
+            }
+            Step::Close => {
+                let _ = tx.send(Message::Close(None)).await;
+                return;
+            }
+            Step::Sleep(duration) => tokio::time::sleep(duration).await,
+            Step::Reject => return,
+        }
+    }
+
+    while let Some(Ok(msg)) = rx.next().await {
+        record_audio(&msg);
+        if matches!(&msg, Message::Binary(data) if data.is_empty()) {
+            let _ = tx.send(Message::Close(None)).await;
+            return;
+        }
+    }
+}
+
+pub fn token(text: &str, is_final: bool) -> Value {
+    json!({ "text": text, "is_final": is_final, "confidence": 1.0 })
+}
+
+pub fn speaker_token(text: &str, speaker: &str, is_final: bool) -> Value {
+    json!({ "text": text, "is_final": is_final, "confidence": 1.0, "speaker": speaker })
+}
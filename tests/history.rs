@@ -0,0 +1,116 @@
+mod common;
+
+use common::{speaker_token, token};
+use serde_json::{Value, json};
+use soniox_live::transcription::history::{TranscriptHistory, format_unix};
+use soniox_live::types::backend::TranscriptUpdate;
+use soniox_live::types::soniox::SonioxTranscriptionResponse;
+use std::path::PathBuf;
+
+fn response(tokens: Vec<Value>) -> TranscriptUpdate {
+    serde_json::from_value::<SonioxTranscriptionResponse>(json!({
+        "tokens": tokens,
+        "final_audio_proc_ms": 0.0,
+        "total_audio_proc_ms": 0.0,
+    }))
+    .unwrap()
+    .into()
+}
+
+fn temp_file(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("{}-{}.jsonl", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn texts(history: &TranscriptHistory) -> Vec<&str> {
+    history.entries().iter().map(|e| e.text.as_str()).collect()
+}
+
+#[test]
+fn keeps_every_final_utterance() {
+    let mut history = TranscriptHistory::new();
+    history.begin_session();
+    for i in 0..50 {
+        history.record(&response(vec![speaker_token(
+            &format!("line {}", i),
+            if i % 2 == 0 { "1" } else { "2" },
+            true,
+        )]));
+    }
+    history.record(&response(vec![token("interim", false)]));
+    assert_eq!(history.entries().len(), 50);
+    assert_eq!(history.entries()[49].text, "line 49");
+}
+
+#[test]
+fn close_starts_a_new_entry() {
+    let mut history = TranscriptHistory::new();
+    history.record(&response(vec![token("Hello", true)]));
+    history.record(&response(vec![token(" there", true)]));
+    history.close();
+    history.record(&response(vec![token(" again", true)]));
+    assert_eq!(texts(&history), ["Hello there", "again"]);
+}
+
+#[test]
+fn search_is_case_insensitive() {
+    let mut history = TranscriptHistory::new();
+    history.record(&response(vec![speaker_token("Good morning", "1", true)]));
+    history.record(&response(vec![speaker_token("Bye", "2", true)]));
+    let found: Vec<_> = history.search("MORNING").collect();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].to_string(), "[1] Good morning");
+    assert_eq!(history.search("").count(), 2);
+}
+
+#[test]
+fn previous_sessions_are_loaded_from_disk() {
+    let path = temp_file("soniox-history");
+    {
+        let mut history = TranscriptHistory::load(&path).unwrap();
+        history.begin_session();
+        history.record(&response(vec![speaker_token("first", "1", true)]));
+        history.record(&response(vec![speaker_token("second", "2", true)]));
+    }
+
+    let mut history = TranscriptHistory::load(&path).unwrap();
+    assert_eq!(texts(&history), ["first", "second"]);
+    assert_eq!(history.sessions(), 1);
+
+    history.begin_session();
+    history.record(&response(vec![token("third", true)]));
+    history.close();
+    let reloaded = TranscriptHistory::load(&path).unwrap();
+    assert_eq!(texts(&reloaded), ["first", "second", "third"]);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn formats_unix_time() {
+    assert_eq!(format_unix(0), "1970-01-01 00:00");
+    assert_eq!(format_unix(1_709_210_096), "2024-02-29 12:34");
+}
+
+#[test]
+fn speaker_names_are_kept_per_session() {
+    let path = temp_file("history-speakers");
+    let mut history = TranscriptHistory::load(&path).unwrap();
+    history.begin_session();
+    history.record(&response(vec![speaker_token("Hi", "1", true)]));
+    history
+        .speakers_mut()
+        .iter_mut()
+        .for_each(|(_, p)| p.name = "Alice".into());
+    let session = history.session();
+    drop(history);
+
+    let history = TranscriptHistory::load(&path).unwrap();
+    assert!(history.speakers().is_empty());
+    let speakers = history.session_speakers(session).unwrap();
+    assert_eq!(speakers.label("1"), "Alice");
+    assert_eq!(history.describe(&history.entries()[0]), "[Alice] Hi");
+
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(path.with_extension("speakers.json"));
+}
@@ -0,0 +1,69 @@
+use eframe::egui::Key;
+use soniox_live::settings::SettingsApp;
+use soniox_live::types::hotkeys::{HotkeyAction, HotkeyBindings, KeyBinding};
+
+#[test]
+fn parses_and_formats_bindings() {
+    let binding: KeyBinding = "ctrl + shift + h".parse().unwrap();
+    assert_eq!(binding, KeyBinding::ctrl_shift(Key::H));
+    assert_eq!(binding.to_string(), "Ctrl+Shift+H");
+
+    let binding: KeyBinding = "Alt+F8".parse().unwrap();
+    assert!(binding.alt && !binding.ctrl && !binding.shift);
+    assert_eq!(binding.key, Key::F8);
+    assert_eq!(binding.to_string().parse::<KeyBinding>().unwrap(), binding);
+}
+
+#[test]
+fn rejects_unknown_keys_and_modifiers() {
+    assert!("Ctrl+".parse::<KeyBinding>().is_err());
+    assert!("Ctrl+NoSuchKey".parse::<KeyBinding>().is_err());
+    assert!("Hyper+A".parse::<KeyBinding>().is_err());
+}
+
+#[test]
+fn unset_binding_survives_save_and_load() {
+    let settings: SettingsApp =
+        toml::from_str("[hotkeys]\nfinalize = \"\"\nopen_settings = \"F9\"").unwrap();
+    let saved = toml::to_string(&settings).unwrap();
+    assert!(saved.contains("finalize = \"\""));
+    let loaded: SettingsApp = toml::from_str(&saved).unwrap();
+    assert_eq!(loaded.hotkeys().get(HotkeyAction::Finalize), None);
+    assert_eq!(
+        loaded.hotkeys().get(HotkeyAction::OpenSettings),
+        Some(KeyBinding::new(Key::F9))
+    );
+    assert_eq!(
+        loaded.hotkeys().get(HotkeyAction::ToggleOverlay),
+        HotkeyBindings::default().toggle_overlay
+    );
+}
+
+#[test]
+fn duplicate_bindings_fail_validation() {
+    let settings: SettingsApp =
+        toml::from_str("[hotkeys]\nclear_subtitles = \"F8\"\nfinalize = \"F8\"").unwrap();
+    assert_eq!(
+        settings
+            .hotkeys()
+            .conflict(HotkeyAction::Finalize, KeyBinding::new(Key::F8)),
+        Some(HotkeyAction::ClearSubtitles)
+    );
+    assert!(settings.validate().is_err());
+    assert!(SettingsApp::default().validate().is_ok());
+}
+
+#[test]
+fn default_bindings_stay_off_common_shortcuts() {
+    let defaults = HotkeyBindings::default();
+    for (action, binding) in defaults.iter() {
+        let name = binding.key.name();
+        assert!(
+            name.starts_with('F') && name.len() > 1,
+            "{} must use a function key",
+            action
+        );
+        assert!(binding.ctrl && binding.shift, "{} needs modifiers", action);
+        assert_eq!(defaults.conflict(action, binding), None);
+    }
+}
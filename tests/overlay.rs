@@ -0,0 +1,87 @@
+use eframe::egui::{Align2, Rect, pos2, vec2};
+use soniox_live::settings::SettingsApp;
+use soniox_live::types::overlay::{OverlayAnchor, OverlayMonitor};
+
+#[test]
+fn numeric_anchors_from_old_configs_still_load() {
+    let expected = [
+        (0, OverlayAnchor::TopLeft),
+        (1, OverlayAnchor::Top),
+        (4, OverlayAnchor::Center),
+        (7, OverlayAnchor::Bottom),
+        (8, OverlayAnchor::BottomRight),
+        (42, OverlayAnchor::Bottom),
+    ];
+    for (index, anchor) in expected {
+        let settings: SettingsApp = toml::from_str(&format!("anchor = {}", index)).unwrap();
+        assert_eq!(settings.anchor(), anchor, "anchor = {}", index);
+    }
+}
+
+#[test]
+fn anchors_are_saved_by_name() {
+    let settings: SettingsApp = toml::from_str("anchor = 2").unwrap();
+    let saved = toml::to_string(&settings).unwrap();
+    assert!(saved.contains("anchor = \"top_right\""));
+
+    let loaded: SettingsApp = toml::from_str(&saved).unwrap();
+    assert_eq!(loaded.anchor(), OverlayAnchor::TopRight);
+    assert!(toml::from_str::<SettingsApp>("anchor = \"somewhere\"").is_err());
+}
+
+#[test]
+fn anchor_matches_its_alignment() {
+    for &anchor in OverlayAnchor::all() {
+        assert_eq!(OverlayAnchor::from_align(anchor.align()), anchor);
+        assert_eq!(anchor.as_str().parse::<OverlayAnchor>(), Ok(anchor));
+    }
+    assert_eq!(OverlayAnchor::Bottom.align(), Align2::CENTER_BOTTOM);
+    assert_eq!(
+        OverlayAnchor::TopLeft.default_offset(30.0),
+        vec2(30.0, 30.0)
+    );
+    assert_eq!(OverlayAnchor::Right.default_offset(30.0), vec2(-30.0, 0.0));
+}
+
+#[test]
+fn overlay_monitor_is_persisted() {
+    let settings: SettingsApp = toml::from_str(
+        "[overlay_monitor]\nname = '\\\\.\\DISPLAY2'\nposition = [2560.0, 0.0]\nsize = [1920.0, 1080.0]",
+    )
+    .unwrap();
+    let monitor = settings.overlay_monitor().unwrap();
+    assert_eq!(monitor.label(), "DISPLAY2");
+    assert_eq!(
+        monitor.rect(),
+        Rect::from_min_size(pos2(2560.0, 0.0), vec2(1920.0, 1080.0))
+    );
+    let saved = toml::to_string(&settings).unwrap();
+    let loaded: SettingsApp = toml::from_str(&saved).unwrap();
+    assert_eq!(loaded.overlay_monitor(), Some(monitor));
+    assert_eq!(SettingsApp::default().overlay_monitor(), None);
+}
+
+fn monitor(name: &str, x: f32, width: f32) -> OverlayMonitor {
+    OverlayMonitor {
+        name: name.to_owned(),
+        position: (x, 0.0),
+        size: (width, 1080.0),
+    }
+}
+
+#[test]
+fn overlay_monitor_is_found_among_attached_displays() {
+    let saved = monitor(r"\\.\DISPLAY2", 1920.0, 1920.0);
+    // Rearranged: the same display now sits left of the primary one.
+    let moved = [
+        monitor(r"\\.\DISPLAY1", 0.0, 2560.0),
+        monitor(r"\\.\DISPLAY2", -1920.0, 1920.0),
+    ];
+    assert_eq!(saved.locate(&moved), Some(&moved[1]));
+    // Renamed, but still at the same place.
+    let renamed = [monitor(r"\\.\DISPLAY3", 1920.0, 1920.0)];
+    assert_eq!(saved.locate(&renamed), Some(&renamed[0]));
+    // Unplugged.
+    let gone = [monitor(r"\\.\DISPLAY1", 0.0, 1920.0)];
+    assert_eq!(saved.locate(&gone), None);
+}
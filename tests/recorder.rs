@@ -0,0 +1,152 @@
+mod common;
+
+use common::{speaker_token, token};
+use serde_json::{Value, json};
+use soniox_live::transcription::clock::AudioClock;
+use soniox_live::transcription::recorder::TranscriptRecorder;
+use soniox_live::types::backend::TranscriptUpdate;
+use soniox_live::types::soniox::SonioxTranscriptionResponse;
+
+fn timed(text: &str, start: u64, end: u64) -> Value {
+    let mut value = token(text, true);
+    value["start_ms"] = start.into();
+    value["end_ms"] = end.into();
+    value
+}
+
+fn response(tokens: Vec<Value>) -> TranscriptUpdate {
+    serde_json::from_value::<SonioxTranscriptionResponse>(json!({
+        "tokens": tokens,
+        "final_audio_proc_ms": 0.0,
+        "total_audio_proc_ms": 0.0,
+    }))
+    .unwrap()
+    .into()
+}
+
+#[test]
+fn ignores_interim_tokens() {
+    let mut recorder = TranscriptRecorder::new();
+    recorder.record(&response(vec![token("maybe", false)]));
+    assert!(recorder.is_empty());
+}
+
+#[test]
+fn writes_srt() {
+    let mut recorder = TranscriptRecorder::new();
+    recorder.record(&response(vec![
+        timed("Hello", 0, 400),
+        timed(" world", 500, 1250),
+    ]));
+    assert_eq!(
+        recorder.to_srt(),
+        "1\n00:00:00,000 --> 00:00:01,250\nHello world\n\n"
+    );
+}
+
+#[test]
+fn writes_vtt_with_voices() {
+    let mut recorder = TranscriptRecorder::new();
+    let mut first = speaker_token("Hi", "1", true);
+    first["start_ms"] = 3_600_000.into();
+    first["end_ms"] = 3_601_500.into();
+    recorder.record(&response(vec![first]));
+    assert_eq!(
+        recorder.to_vtt(),
+        "WEBVTT\n\n01:00:00.000 --> 01:00:01.500\n<v 1>Hi\n\n"
+    );
+}
+
+#[test]
+fn splits_on_speaker_and_pause() {
+    let mut recorder = TranscriptRecorder::new();
+    let mut other = speaker_token(" Yes", "2", true);
+    other["start_ms"] = 600.into();
+    other["end_ms"] = 900.into();
+    recorder.record(&response(vec![
+        timed("Ready", 0, 500),
+        other,
+        timed(" Later", 5000, 5400),
+    ]));
+    let texts: Vec<_> = recorder.cues().iter().map(|c| c.text.as_str()).collect();
+    assert_eq!(texts, ["Ready", "Yes", "Later"]);
+}
+
+#[test]
+fn splits_long_cues_on_word_boundary() {
+    let mut recorder = TranscriptRecorder::new();
+    let words: Vec<_> = (0..40)
+        .map(|i| timed(" word", i * 100, i * 100 + 90))
+        .collect();
+    recorder.record(&response(words));
+    assert!(recorder.cues().len() > 1);
+    for cue in recorder.cues() {
+        assert!(cue.text.chars().count() <= 90);
+        assert!(cue.text.starts_with("word"));
+    }
+}
+
+#[test]
+fn skips_original_when_translating() {
+    let mut recorder = TranscriptRecorder::new();
+    let mut original = timed("Hola", 0, 300);
+    original["translation_status"] = "original".into();
+    let mut translation = timed("Hello", 0, 300);
+    translation["translation_status"] = "translation".into();
+    recorder.record(&response(vec![original, translation]));
+    assert_eq!(recorder.cues()[0].text, "Hello");
+}
+
+#[test]
+fn gated_gap_keeps_capture_time() {
+    let clock = AudioClock::new(1000, 1);
+    let speech = clock.capture(500);
+    clock.upload(speech, 500);
+    // Two seconds of silence the voice gate never uploaded.
+    clock.capture(2000);
+    let speech = clock.capture(500);
+    clock.upload(speech, 500);
+
+    let mut update = response(vec![timed("Before.", 0, 400), timed("After", 600, 900)]);
+    clock.stamp(&mut update, 0);
+    let mut recorder = TranscriptRecorder::new();
+    recorder.record(&update);
+
+    let times: Vec<_> = recorder
+        .cues()
+        .iter()
+        .map(|c| (c.start_ms, c.end_ms))
+        .collect();
+    assert_eq!(times, [(0, 400), (2600, 2900)]);
+}
+
+#[test]
+fn pause_and_replayed_backlog_keep_capture_time() {
+    let clock = AudioClock::new(1000, 1);
+    let first = clock.capture(1000);
+    clock.upload(first, 1000);
+    clock.skip(std::time::Duration::from_secs(5));
+    let second = clock.capture(1000);
+    clock.upload(second, 1000);
+
+    // A session that connected after the outage starts with the replayed backlog.
+    assert_eq!(clock.capture_ms(500, 0.0), 500.0);
+    assert_eq!(clock.capture_ms(1000, 250.0), 6250.0);
+}
+
+#[test]
+fn export_writes_requested_formats() {
+    let dir = std::env::temp_dir().join(format!("soniox-export-{}", std::process::id()));
+    let mut recorder = TranscriptRecorder::new();
+    assert!(recorder.export(&dir, true, true).unwrap().is_empty());
+
+    recorder.record(&response(vec![timed("Hello", 0, 400)]));
+    let paths = recorder.export(&dir, true, true).unwrap();
+    assert_eq!(paths.len(), 2);
+    assert!(
+        std::fs::read_to_string(&paths[1])
+            .unwrap()
+            .starts_with("WEBVTT")
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,68 @@
+use soniox_live::soniox::retry::RetryPolicy;
+use std::time::Duration;
+
+#[test]
+fn delay_grows_exponentially_up_to_cap() {
+    let policy = RetryPolicy {
+        initial_delay_ms: 100,
+        multiplier: 2.0,
+        max_delay_ms: 1000,
+        jitter: 0.0,
+        ..Default::default()
+    };
+
+    assert_eq!(policy.delay(1), Duration::from_millis(100));
+    assert_eq!(policy.delay(2), Duration::from_millis(200));
+    assert_eq!(policy.delay(4), Duration::from_millis(800));
+    assert_eq!(policy.delay(5), Duration::from_millis(1000));
+    assert_eq!(policy.delay(500), Duration::from_millis(1000));
+}
+
+#[test]
+fn jitter_stays_within_bounds() {
+    let policy = RetryPolicy {
+        initial_delay_ms: 1000,
+        multiplier: 1.0,
+        max_delay_ms: 10_000,
+        jitter: 0.5,
+        ..Default::default()
+    };
+
+    for _ in 0..100 {
+        let delay = policy.delay(1).as_millis();
+        assert!(
+            (500..=1500).contains(&delay),
+            "delay {} out of range",
+            delay
+        );
+    }
+}
+
+#[test]
+fn attempts_are_limited_unless_forever() {
+    let mut policy = RetryPolicy {
+        max_attempts: 3,
+        ..Default::default()
+    };
+    assert!(policy.allows(3));
+    assert!(!policy.allows(4));
+
+    policy.retry_forever = true;
+    assert!(policy.allows(u32::MAX));
+}
+
+#[test]
+fn rejects_invalid_policies() {
+    assert!(RetryPolicy::default().validate().is_ok());
+    let shrinking = RetryPolicy {
+        multiplier: 0.5,
+        ..Default::default()
+    };
+    assert!(shrinking.validate().is_err());
+    let inverted = RetryPolicy {
+        initial_delay_ms: 5000,
+        max_delay_ms: 1000,
+        ..Default::default()
+    };
+    assert!(inverted.validate().is_err());
+}
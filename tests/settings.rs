@@ -0,0 +1,161 @@
+use eframe::egui::{Align2, Color32, Stroke, vec2};
+use soniox_live::settings::{SettingsApp, SettingsChange};
+
+fn edited(toml: &str) -> SettingsApp {
+    toml::from_str(toml).unwrap()
+}
+
+#[test]
+fn same_settings_need_nothing() {
+    let settings = SettingsApp::default();
+    assert_eq!(settings.diff(&settings.clone()), SettingsChange::None);
+}
+
+#[test]
+fn appearance_changes_are_visual() {
+    let base = SettingsApp::default();
+    for toml in [
+        "font_size = 30",
+        "text_color = [255, 255, 255]",
+        "max_blocks = 5",
+        "anchor = 1",
+        "confidence_mode = \"dim\"",
+        "enable_outline = true",
+        "background_color = [20, 20, 60, 200]",
+        // Only part of the request while translation is on.
+        "target_language = \"de\"",
+    ] {
+        assert_eq!(base.diff(&edited(toml)), SettingsChange::Visual, "{}", toml);
+    }
+}
+
+#[test]
+fn request_changes_restart_the_worker() {
+    let base = SettingsApp::default();
+    for toml in [
+        "language_hints = [\"fr\"]",
+        "context = \"a football match\"",
+        "api_key = \"another\"",
+        "enable_translate = true",
+        "enable_speakers = false",
+        "enable_endpoint_detection = false",
+    ] {
+        assert_eq!(base.diff(&edited(toml)), SettingsChange::Worker, "{}", toml);
+    }
+
+    let translating = edited("enable_translate = true");
+    let retargeted = edited("enable_translate = true\ntarget_language = \"de\"");
+    assert_eq!(translating.diff(&retargeted), SettingsChange::Worker);
+}
+
+#[test]
+fn audio_changes_restart_everything() {
+    let base = SettingsApp::default();
+    for toml in [
+        "audio_source = \"microphone\"",
+        "sample_rate = 48000",
+        "enable_vad = true",
+        "mic_gain = 2.0",
+    ] {
+        assert_eq!(
+            base.diff(&edited(toml)),
+            SettingsChange::Restart,
+            "{}",
+            toml
+        );
+    }
+    let both = edited("sample_rate = 48000\nfont_size = 30\ncontext = \"x\"");
+    assert_eq!(base.diff(&both), SettingsChange::Restart);
+}
+
+#[test]
+fn anchor_round_trips_through_set_anchor() {
+    let mut settings = SettingsApp::default();
+    for align in [
+        Align2::LEFT_TOP,
+        Align2::CENTER_TOP,
+        Align2::RIGHT_CENTER,
+        Align2::CENTER_CENTER,
+        Align2::LEFT_BOTTOM,
+        Align2::RIGHT_BOTTOM,
+    ] {
+        settings.set_anchor(align, vec2(12.0, -40.0));
+        assert_eq!(settings.get_anchor(), (align, vec2(12.0, -40.0)));
+    }
+}
+
+#[test]
+fn subtitle_width_is_validated() {
+    assert!(edited("subtitle_width = 900.0").validate().is_ok());
+    assert!(edited("subtitle_width = 10.0").validate().is_err());
+    assert_eq!(
+        SettingsApp::default().diff(&edited("subtitle_width = 900.0")),
+        SettingsChange::Visual
+    );
+}
+
+#[test]
+fn subtitle_styling_defaults_match_the_old_look() {
+    let settings = edited("enable_background = true\nfont_size = 24");
+    assert_eq!(
+        settings.get_background_color(),
+        Color32::from_black_alpha(155)
+    );
+    assert_eq!(settings.corner_radius(), 12.0);
+    assert_eq!(settings.line_spacing(), 4.0);
+    assert_eq!(settings.outline(), None);
+    assert_eq!(settings.shadow(), None);
+    assert_eq!(
+        edited("enable_background = false").get_background_color(),
+        Color32::TRANSPARENT
+    );
+}
+
+#[test]
+fn subtitle_styling_is_persisted() {
+    let settings = edited(
+        "background_color = [10, 20, 30, 128]\n\
+         corner_radius = 4.0\n\
+         line_spacing = 10.0\n\
+         enable_outline = true\n\
+         outline_width = 2.0\n\
+         outline_color = [255, 255, 255, 255]\n\
+         enable_shadow = true\n\
+         shadow_offset = [3.0, -1.0]\n\
+         shadow_color = [0, 0, 0, 100]",
+    );
+    let loaded = edited(&toml::to_string(&settings).unwrap());
+    assert!(loaded == settings);
+    assert_eq!(
+        loaded.get_background_color(),
+        Color32::from_rgba_unmultiplied(10, 20, 30, 128)
+    );
+    assert_eq!(loaded.corner_radius(), 4.0);
+    assert_eq!(loaded.line_spacing(), 10.0);
+    assert_eq!(loaded.outline(), Some(Stroke::new(2.0, Color32::WHITE)));
+    assert_eq!(
+        loaded.shadow(),
+        Some((vec2(3.0, -1.0), Color32::from_black_alpha(100)))
+    );
+}
+
+#[test]
+fn subtitle_styling_is_validated() {
+    assert!(edited("outline_width = 2.0").validate().is_ok());
+    assert!(edited("outline_width = 50.0").validate().is_err());
+    assert!(edited("corner_radius = -1.0").validate().is_err());
+    assert!(edited("line_spacing = -4.0").validate().is_err());
+}
+
+#[test]
+fn invalid_settings_are_still_saved() {
+    let path = std::env::temp_dir().join(format!("soniox-save-{}.toml", std::process::id()));
+    let settings = edited("outline_width = 50.0\nfont_size = 31");
+    assert!(settings.validate().is_err());
+    settings.save(path.to_str().unwrap()).unwrap();
+
+    let loaded = SettingsApp::new(path.to_str().unwrap()).unwrap();
+    assert_eq!(loaded.font_size(), 31.0);
+    assert!(loaded.validate().is_err());
+    let _ = std::fs::remove_file(path);
+}
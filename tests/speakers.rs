@@ -0,0 +1,27 @@
+use soniox_live::types::speakers::SpeakerRegistry;
+
+#[test]
+fn label_falls_back_to_id() {
+    let mut registry = SpeakerRegistry::default();
+    assert_eq!(registry.label("1"), "1");
+    assert!(registry.register("1"));
+    assert_eq!(registry.label("1"), "1");
+
+    registry
+        .iter_mut()
+        .for_each(|(_, p)| p.name = " Alice ".into());
+    assert_eq!(registry.label("1"), "Alice");
+}
+
+#[test]
+fn new_speakers_get_distinct_colors() {
+    let mut registry = SpeakerRegistry::default();
+    registry.register("1");
+    registry.register("2");
+    assert!(!registry.register("1"));
+    assert_eq!(registry.len(), 2);
+    assert_ne!(
+        registry.get("1").unwrap().color,
+        registry.get("2").unwrap().color
+    );
+}
@@ -0,0 +1,337 @@
+mod common;
+
+use common::{speaker_token, token};
+use serde_json::{Value, json};
+use soniox_live::transcription::replicas::prepare_replicas;
+use soniox_live::transcription::store::TranscriptionStore;
+use soniox_live::types::backend::TranscriptUpdate;
+use soniox_live::types::soniox::SonioxTranscriptionResponse;
+use soniox_live::types::subtitles::{ConfidenceMode, ConfidenceStyle};
+
+fn response(tokens: Vec<Value>) -> TranscriptUpdate {
+    serde_json::from_value::<SonioxTranscriptionResponse>(json!({
+        "tokens": tokens,
+        "final_audio_proc_ms": 0.0,
+        "total_audio_proc_ms": 0.0,
+    }))
+    .unwrap()
+    .into()
+}
+
+fn texts(store: &TranscriptionStore) -> Vec<&str> {
+    store.blocks.iter().map(|b| b.text()).collect()
+}
+
+#[test]
+fn final_tokens_accumulate_in_one_block() {
+    let mut store = TranscriptionStore::new(3);
+    store.update(response(vec![token("Hello", true), token(" world", true)]));
+    store.update(response(vec![token("!", true)]));
+
+    assert_eq!(texts(&store), vec!["Hello world!"]);
+    assert!(store.interim_blocks.is_empty());
+    assert!(store.last_activity().is_some());
+}
+
+#[test]
+fn speaker_change_starts_new_block() {
+    let mut store = TranscriptionStore::new(3);
+    store.update(response(vec![
+        speaker_token("Hi", "1", true),
+        speaker_token("Hey", "2", true),
+    ]));
+
+    assert_eq!(texts(&store), vec!["Hi", "Hey"]);
+    assert_eq!(store.blocks[0].speaker(), Some("1"));
+    assert_eq!(store.blocks[1].speaker(), Some("2"));
+}
+
+#[test]
+fn interim_tokens_are_replaced_by_next_update() {
+    let mut store = TranscriptionStore::new(3);
+    store.update(response(vec![token("Hel", false)]));
+    assert_eq!(store.interim_blocks.len(), 1);
+    assert_eq!(store.interim_blocks[0].text(), "Hel");
+
+    store.update(response(vec![token("Hello", true), token(" th", false)]));
+    assert_eq!(texts(&store), vec!["Hello"]);
+    assert_eq!(store.interim_blocks.len(), 1);
+    assert_eq!(store.interim_blocks[0].text(), " th");
+}
+
+#[test]
+fn original_tokens_are_skipped_for_translation() {
+    let mut store = TranscriptionStore::new(3);
+    let mut original = token("Hola", true);
+    original["translation_status"] = json!("original");
+    let mut translated = token("Hello", true);
+    translated["translation_status"] = json!("translation");
+    store.update(response(vec![original, translated]));
+
+    assert_eq!(texts(&store), vec!["Hello"]);
+}
+
+#[test]
+fn overflow_drops_oldest_blocks() {
+    let mut store = TranscriptionStore::new(2);
+    store.update(response(vec![
+        speaker_token("a", "1", true),
+        speaker_token("b", "2", true),
+        speaker_token("c", "1", true),
+    ]));
+
+    assert_eq!(texts(&store), vec!["b", "c"]);
+    store.resize(1);
+    assert_eq!(texts(&store), vec!["c"]);
+}
+
+#[test]
+fn separator_flushes_interim_text() {
+    let mut store = TranscriptionStore::new(3);
+    store.update(response(vec![token("Hello ", true), token("wor", false)]));
+    store.ensure_separator();
+
+    assert!(store.interim_blocks.is_empty());
+    assert_eq!(store.blocks.back().map(|b| b.text()), Some("wor...    "));
+}
+
+#[test]
+fn empty_update_keeps_activity_untouched() {
+    let mut store = TranscriptionStore::new(3);
+    store.update(response(vec![]));
+    assert!(store.last_activity().is_none());
+    assert!(store.blocks.is_empty());
+}
+
+#[test]
+fn original_is_kept_above_translation_when_enabled() {
+    let mut store = TranscriptionStore::new(3);
+    store.set_show_original(true);
+    let mut original = speaker_token("Hola", "1", true);
+    original["translation_status"] = json!("original");
+    let mut translated = speaker_token("Hello", "1", true);
+    translated["translation_status"] = json!("translation");
+    let mut interim = speaker_token(" amigo", "1", false);
+    interim["translation_status"] = json!("original");
+    store.update(response(vec![original, translated, interim]));
+
+    assert_eq!(texts(&store), vec!["Hello"]);
+    assert_eq!(store.blocks[0].original(), "Hola");
+    assert_eq!(store.interim_blocks[0].original(), " amigo");
+
+    let replicas = prepare_replicas(&store);
+    assert_eq!(replicas.len(), 1);
+    let originals: Vec<_> = replicas[0].originals.iter().map(|e| e.text).collect();
+    assert_eq!(originals, vec!["Hola", " amigo"]);
+    assert_eq!(replicas[0].elements.len(), 1);
+}
+
+#[test]
+fn consecutive_translated_sentences_stay_aligned() {
+    let mut store = TranscriptionStore::new(3);
+    store.set_show_original(true);
+    let pair = |original: &str, translation: &str| {
+        let mut source = token(original, true);
+        source["translation_status"] = json!("original");
+        let mut target = token(translation, true);
+        target["translation_status"] = json!("translation");
+        vec![source, target]
+    };
+    store.update(response(pair("Hola.", "Hello.")));
+    store.update(response(pair(" Adiós.", " Bye.")));
+
+    let originals: Vec<_> = store.blocks.iter().map(|b| b.original()).collect();
+    assert_eq!(originals, vec!["Hola.", " Adiós."]);
+    assert_eq!(texts(&store), vec!["Hello.", " Bye."]);
+}
+
+#[test]
+fn late_translations_follow_their_originals() {
+    let mut store = TranscriptionStore::new(3);
+    store.set_show_original(true);
+    let with_status = |text: &str, status: &str| {
+        let mut value = token(text, true);
+        value["translation_status"] = json!(status);
+        value
+    };
+    store.update(response(vec![
+        with_status("Sí", "original"),
+        token("<end>", true),
+        with_status("No", "original"),
+    ]));
+    store.update(response(vec![
+        with_status("Yes", "translation"),
+        with_status(" No", "original"),
+        with_status("No", "translation"),
+    ]));
+
+    let originals: Vec<_> = store.blocks.iter().map(|b| b.original()).collect();
+    assert_eq!(originals, vec!["Sí", "No No"]);
+    assert_eq!(texts(&store), vec!["Yes", "No"]);
+}
+
+#[test]
+fn two_way_directions_get_separate_blocks() {
+    let mut store = TranscriptionStore::new(3);
+    let translation = |text: &str, source: &str, target: &str| {
+        let mut value = token(text, true);
+        value["translation_status"] = json!("translation");
+        value["source_language"] = json!(source);
+        value["language"] = json!(target);
+        value
+    };
+    store.update(response(vec![
+        translation("Hello", "es", "en"),
+        translation(" friend", "es", "en"),
+        translation("Hola", "en", "es"),
+    ]));
+
+    assert_eq!(texts(&store), vec!["Hello friend", "Hola"]);
+    let directions: Vec<_> = store
+        .blocks
+        .iter()
+        .map(|b| b.direction().map(|(s, t)| (s.code(), t.code())))
+        .collect();
+    assert_eq!(directions, vec![Some(("es", "en")), Some(("en", "es"))]);
+    assert_eq!(prepare_replicas(&store).len(), 2);
+}
+
+#[test]
+fn token_confidence_is_kept_per_span() {
+    let mut store = TranscriptionStore::new(3);
+    let mut unsure = token(" wrld", true);
+    unsure["confidence"] = json!(0.3);
+    store.update(response(vec![token("Hello", true), unsure]));
+
+    let replicas = prepare_replicas(&store);
+    let segments = replicas[0].elements[0].segments();
+    assert_eq!(segments, vec![("Hello", 1.0), (" wrld", 0.3)]);
+
+    store.ensure_separator();
+    let replicas = prepare_replicas(&store);
+    let segments = replicas[0].elements[0].segments();
+    assert_eq!(
+        segments,
+        vec![("Hello", 1.0), (" wrld", 0.3), ("...    ", 1.0)]
+    );
+}
+
+#[test]
+fn confidence_style_marks_and_hides() {
+    let style = ConfidenceStyle {
+        mode: ConfidenceMode::Dim,
+        threshold: 0.6,
+        floor: 0.2,
+    };
+    assert!(style.is_marked(0.5));
+    assert!(!style.is_marked(0.9));
+    assert!(style.is_hidden(0.1));
+    assert!(!style.is_hidden(0.5));
+
+    let off = ConfidenceStyle {
+        mode: ConfidenceMode::Off,
+        ..style
+    };
+    assert!(!off.is_marked(0.1));
+}
+
+#[test]
+fn language_switch_starts_new_block() {
+    let mut store = TranscriptionStore::new(3);
+    let spoken = |text: &str, language: &str| {
+        let mut value = token(text, true);
+        value["language"] = json!(language);
+        value
+    };
+    store.update(response(vec![
+        spoken("Hola", "es"),
+        spoken(" amigos.", "es"),
+        spoken(" Hello", "en"),
+    ]));
+
+    assert_eq!(texts(&store), vec!["Hola amigos.", " Hello"]);
+    let replicas = prepare_replicas(&store);
+    let languages: Vec<_> = replicas
+        .iter()
+        .map(|r| r.language.map(|l| l.code()))
+        .collect();
+    assert_eq!(languages, vec![Some("es"), Some("en")]);
+}
+
+#[test]
+fn endpoint_closes_block_without_text() {
+    let mut store = TranscriptionStore::new(3);
+    store.update(response(vec![
+        token("Yes", true),
+        token("<end>", true),
+        token(" no", true),
+    ]));
+
+    assert_eq!(texts(&store), vec!["Yes", " no"]);
+}
+
+#[test]
+fn endpoint_splits_translations_with_hidden_originals() {
+    let mut store = TranscriptionStore::new(3);
+    let with_status = |text: &str, status: &str| {
+        let mut value = token(text, true);
+        value["translation_status"] = json!(status);
+        value
+    };
+    store.update(response(vec![
+        with_status("Sí", "original"),
+        token("<end>", true),
+        with_status("Yes", "translation"),
+    ]));
+    store.update(response(vec![
+        with_status("No", "original"),
+        token("<end>", true),
+        with_status(" no", "translation"),
+    ]));
+
+    assert_eq!(texts(&store), vec!["Yes", " no"]);
+    let replicas = prepare_replicas(&store);
+    assert!(replicas.iter().all(|r| r.originals.is_empty()));
+}
+
+#[test]
+fn sentence_end_starts_new_block() {
+    let mut store = TranscriptionStore::new(3);
+    store.update(response(vec![
+        token("One.", true),
+        token(" Two", true),
+        token(" three?", true),
+    ]));
+
+    assert_eq!(texts(&store), vec!["One.", " Two three?"]);
+}
+
+#[test]
+fn long_blocks_split_between_words() {
+    let mut store = TranscriptionStore::new(10);
+    store.set_max_chars(10);
+    store.update(response(vec![
+        token("abcdefgh", true),
+        token("ij", true),
+        token("kl", true),
+        token(" next", true),
+    ]));
+
+    assert_eq!(texts(&store), vec!["abcdefghijkl", " next"]);
+}
+
+#[test]
+fn unspaced_text_splits_on_grapheme_boundary() {
+    let mut store = TranscriptionStore::new(10);
+    store.set_max_chars(4);
+    // "e" + combining acute is a single grapheme and must stay together.
+    store.update(response(vec![
+        token("ありが", true),
+        token("とう", true),
+        token("e", true),
+        token("\u{301}", true),
+        token("す", true),
+    ]));
+
+    assert_eq!(texts(&store), vec!["ありがとうe\u{301}", "す"]);
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use common::{speaker_token, token};
+use serde_json::{Value, json};
+use soniox_live::transcription::transcript_log::{TranscriptLog, session_path};
+use soniox_live::types::backend::TranscriptUpdate;
+use soniox_live::types::soniox::SonioxTranscriptionResponse;
+use soniox_live::types::transcript::TranscriptLogFormat;
+use std::path::{Path, PathBuf};
+
+fn response(tokens: Vec<Value>) -> TranscriptUpdate {
+    serde_json::from_value::<SonioxTranscriptionResponse>(json!({
+        "tokens": tokens,
+        "final_audio_proc_ms": 0.0,
+        "total_audio_proc_ms": 0.0,
+    }))
+    .unwrap()
+    .into()
+}
+
+fn with_status(mut value: Value, status: &str, lang: &str) -> Value {
+    value["translation_status"] = json!(status);
+    value["language"] = json!(lang);
+    if status == "translation" {
+        value["source_language"] = json!("es");
+    }
+    value
+}
+
+fn lines(log: &TranscriptLog<Vec<u8>>) -> Vec<Value> {
+    String::from_utf8_lossy(log.get_ref())
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect()
+}
+
+#[test]
+fn writes_utterance_when_sentence_ends() {
+    let mut log = TranscriptLog::new(Vec::new(), TranscriptLogFormat::JsonLines);
+    log.record(&response(vec![
+        speaker_token("Hello", "1", true),
+        speaker_token(" there.", "1", true),
+        speaker_token(" maybe", "1", false),
+    ]))
+    .unwrap();
+    assert!(lines(&log).is_empty());
+
+    log.record(&response(vec![speaker_token(" Next", "1", true)]))
+        .unwrap();
+    let written = lines(&log);
+    assert_eq!(written.len(), 1);
+    assert_eq!(written[0]["text"], "Hello there.");
+    assert_eq!(written[0]["speaker"], "1");
+    assert!(written[0]["translation"].is_null());
+    assert!(written[0]["timestamp"].as_u64().unwrap() > 0);
+}
+
+#[test]
+fn speaker_change_ends_utterance() {
+    let mut log = TranscriptLog::new(Vec::new(), TranscriptLogFormat::JsonLines);
+    log.record(&response(vec![
+        speaker_token("one", "1", true),
+        speaker_token(" two", "2", true),
+    ]))
+    .unwrap();
+    log.flush().unwrap();
+    let texts: Vec<_> = lines(&log).iter().map(|l| l["text"].clone()).collect();
+    assert_eq!(texts, [json!("one"), json!("two")]);
+}
+
+#[test]
+fn pairs_original_with_translation() {
+    let mut log = TranscriptLog::new(Vec::new(), TranscriptLogFormat::JsonLines);
+    log.record(&response(vec![with_status(
+        token("Hola.", true),
+        "original",
+        "es",
+    )]))
+    .unwrap();
+    log.record(&response(vec![
+        with_status(token("Otra", true), "original", "es"),
+        with_status(token("Hello.", true), "translation", "en"),
+    ]))
+    .unwrap();
+    log.record(&response(vec![with_status(
+        token(" cosa.", true),
+        "original",
+        "es",
+    )]))
+    .unwrap();
+    log.record(&response(vec![with_status(
+        token("Something else.", true),
+        "translation",
+        "en",
+    )]))
+    .unwrap();
+    log.flush().unwrap();
+
+    let written = lines(&log);
+    assert_eq!(written.len(), 2, "{:?}", written);
+    assert_eq!(written[0]["text"], "Hola.");
+    assert_eq!(written[0]["translation"], "Hello.");
+    assert_eq!(written[0]["language"], "es");
+    assert_eq!(written[0]["source_language"], "es");
+    assert_eq!(written[1]["text"], "Otra cosa.");
+    assert_eq!(written[1]["translation"], "Something else.");
+}
+
+#[test]
+fn plain_text_format() {
+    let mut log = TranscriptLog::new(Vec::new(), TranscriptLogFormat::PlainText);
+    log.record(&response(vec![speaker_token("Hi.", "2", true)]))
+        .unwrap();
+    log.flush().unwrap();
+    let text = String::from_utf8_lossy(log.get_ref()).to_string();
+    assert!(text.starts_with('['));
+    assert!(text.ends_with("] Speaker 2: Hi.\n"), "{}", text);
+}
+
+#[test]
+fn rotates_file_per_session() {
+    assert_eq!(
+        session_path(Path::new("logs/out"), TranscriptLogFormat::JsonLines, 42),
+        PathBuf::from("logs/out-42.jsonl")
+    );
+    assert_eq!(
+        session_path(Path::new("out.log"), TranscriptLogFormat::PlainText, 7),
+        PathBuf::from("out-7.txt")
+    );
+
+    let dir = std::env::temp_dir().join(format!("soniox-log-{}", std::process::id()));
+    let (mut log, path) =
+        TranscriptLog::create(dir.join("t"), TranscriptLogFormat::JsonLines, 1).unwrap();
+    log.record(&response(vec![token("saved", true)])).unwrap();
+    drop(log);
+    assert!(std::fs::read_to_string(path).unwrap().contains("\"saved\""));
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,53 @@
+use soniox_live::transcription::vad::{PreRoll, VoiceGate, rms};
+
+fn tone(amplitude: i16, len: usize) -> Vec<i16> {
+    (0..len)
+        .map(|i| if i % 2 == 0 { amplitude } else { -amplitude })
+        .collect()
+}
+
+#[test]
+fn rms_of_square_wave_is_its_amplitude() {
+    assert_eq!(rms(&tone(1000, 100)), 1000.0);
+    assert_eq!(rms(&[]), 0.0);
+}
+
+#[test]
+fn silence_is_gated() {
+    let mut gate = VoiceGate::new(-40.0, 0, 16000, 1);
+    assert!(!gate.process(&tone(10, 160)));
+    assert!(!gate.is_active());
+}
+
+#[test]
+fn speech_opens_gate_and_hangover_keeps_it_open() {
+    let mut gate = VoiceGate::new(-40.0, 20, 16000, 1);
+    assert!(gate.process(&tone(10_000, 160)));
+
+    // 20 ms at 16 kHz is 320 samples: two quiet chunks pass, the third is gated.
+    assert!(gate.process(&tone(0, 160)));
+    assert!(gate.process(&tone(0, 160)));
+    assert!(!gate.process(&tone(0, 160)));
+
+    assert!(gate.process(&tone(10_000, 160)));
+}
+
+#[test]
+fn pre_roll_keeps_the_latest_chunks() {
+    let mut pre_roll = PreRoll::new(320);
+    assert_eq!(pre_roll.push(tone(1, 160)), None);
+    assert_eq!(pre_roll.push(tone(2, 160)), None);
+    assert_eq!(pre_roll.push(tone(3, 160)), Some(tone(1, 160)));
+    assert_eq!(pre_roll.len(), 320);
+
+    let drained: Vec<_> = pre_roll.drain().collect();
+    assert_eq!(drained, vec![tone(2, 160), tone(3, 160)]);
+    assert!(pre_roll.is_empty());
+}
+
+#[test]
+fn pre_roll_without_capacity_keeps_nothing() {
+    let mut pre_roll = PreRoll::new(0);
+    assert_eq!(pre_roll.push(tone(1, 160)), Some(tone(1, 160)));
+    assert_eq!(pre_roll.drain().count(), 0);
+}
@@ -0,0 +1,652 @@
+mod common;
+
+use common::{MockServer, Step, token};
+use soniox_live::errors::SonioxLiveErrors;
+use soniox_live::settings::SettingsApp;
+use soniox_live::soniox::backend::SonioxBackend;
+use soniox_live::soniox::backlog::AudioBacklog;
+use soniox_live::soniox::retry::RetryPolicy;
+use soniox_live::soniox::worker::{KEEPALIVE_INTERVAL, SonioxWorker, WorkerTask};
+use soniox_live::transcription::clock::AudioClock;
+use soniox_live::types::audio::AudioSample;
+use soniox_live::types::backend::TranslationStatus;
+use soniox_live::types::events::{SonioxEvent, WorkerCommand};
+use soniox_live::types::languages::LanguageHint;
+use soniox_live::types::soniox::SonioxTranscriptionRequest;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::{Receiver, Sender, channel};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+
+const EVENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct Harness {
+    rx_event: Receiver<SonioxEvent>,
+    commands: Sender<WorkerCommand>,
+    worker: JoinHandle<Result<(), SonioxLiveErrors>>,
+    feeder: JoinHandle<()>,
+}
+
+fn soniox_backend(url: &str, api_key: &str) -> SonioxBackend {
+    let settings: SettingsApp = toml::from_str(&format!(
+        "endpoint = \"{}\"\napi_key = \"{}\"",
+        url, api_key
+    ))
+    .unwrap();
+    let request = SonioxTranscriptionRequest {
+        api_key: settings.api_key(),
+        model: settings.model(),
+        audio_format: "pcm_s16le",
+        sample_rate: Some(16000),
+        num_channels: Some(1),
+        ..Default::default()
+    };
+    SonioxBackend::new(&settings, request)
+}
+
+impl Harness {
+    fn spawn(server: &MockServer) -> Self {
+        Self::spawn_with(&server.url(), RetryPolicy::default())
+    }
+
+    fn spawn_with(url: &str, retry_policy: RetryPolicy) -> Self {
+        let (mut harness, tx_audio, mut rx_recycle) = Self::spawn_manual(
+            url,
+            retry_policy,
+            AudioBacklog::new(16000),
+            KEEPALIVE_INTERVAL,
+        );
+        harness.feeder = tokio::spawn(async move {
+            loop {
+                let mut buffer = rx_recycle.try_recv().unwrap_or_default();
+                buffer.resize(160, 1);
+                if tx_audio.send(buffer).await.is_err() {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        });
+        harness
+    }
+
+    fn spawn_manual(
+        url: &str,
+        retry_policy: RetryPolicy,
+        backlog: AudioBacklog,
+        keepalive: Duration,
+    ) -> (Self, Sender<AudioSample>, Receiver<AudioSample>) {
+        Self::spawn_clocked(url, retry_policy, backlog, keepalive, None)
+    }
+
+    fn spawn_clocked(
+        url: &str,
+        retry_policy: RetryPolicy,
+        backlog: AudioBacklog,
+        keepalive: Duration,
+        clock: Option<Arc<AudioClock>>,
+    ) -> (Self, Sender<AudioSample>, Receiver<AudioSample>) {
+        let backend = soniox_backend(url, "test-key");
+
+        let (tx_audio, rx_audio) = channel::<AudioSample>(256);
+        let (tx_recycle, rx_recycle) = channel::<AudioSample>(256);
+        let (tx_event, rx_event) = channel::<SonioxEvent>(128);
+        let (commands, rx_command) = channel::<WorkerCommand>(8);
+        let worker = SonioxWorker::new(
+            rx_audio,
+            rx_command,
+            tx_recycle,
+            tx_event,
+            retry_policy,
+            backlog,
+        )
+        .with_keepalive_interval(keepalive);
+        let worker = match clock {
+            Some(clock) => worker.with_clock(clock),
+            None => worker,
+        };
+        let worker = tokio::spawn(async move { worker.run(&backend).await });
+
+        let harness = Self {
+            rx_event,
+            commands,
+            worker,
+            feeder: tokio::spawn(async {}),
+        };
+        (harness, tx_audio, rx_recycle)
+    }
+
+    async fn next_event(&mut self) -> SonioxEvent {
+        timeout(EVENT_TIMEOUT, self.rx_event.recv())
+            .await
+            .expect("timed out waiting for worker event")
+            .expect("worker event channel closed")
+    }
+
+    async fn wait_for(&mut self, predicate: impl Fn(&SonioxEvent) -> bool) -> SonioxEvent {
+        loop {
+            let event = self.next_event().await;
+            if predicate(&event) {
+                return event;
+            }
+        }
+    }
+
+    async fn stop(self) -> Result<(), SonioxLiveErrors> {
+        self.feeder.abort();
+        timeout(EVENT_TIMEOUT, self.worker)
+            .await
+            .expect("worker didn't stop")
+            .expect("worker panicked")
+    }
+}
+
+#[tokio::test]
+async fn sends_config_and_forwards_tokens() {
+    let server = MockServer::start(vec![vec![
+        Step::WaitAudio(320),
+        Step::Tokens(serde_json::json!([token("Hello", true)])),
+    ]])
+    .await;
+    let mut harness = Harness::spawn(&server);
+
+    let event = harness.next_event().await;
+    assert!(matches!(event, SonioxEvent::Connected(true)));
+    let event = harness
+        .wait_for(|e| matches!(e, SonioxEvent::Transcription(_)))
+        .await;
+    let SonioxEvent::Transcription(response) = event else {
+        unreachable!()
+    };
+    assert_eq!(response.tokens.len(), 1);
+    assert_eq!(response.tokens[0].text, "Hello");
+    assert!(response.tokens[0].is_final);
+
+    let recorded = server.recorded.lock().unwrap();
+    assert_eq!(recorded.configs.len(), 1);
+    assert_eq!(recorded.configs[0]["api_key"], "test-key");
+    assert_eq!(recorded.configs[0]["audio_format"], "pcm_s16le");
+    assert!(recorded.audio_bytes[0] >= 320);
+}
+
+#[tokio::test]
+async fn soniox_tokens_are_normalized() {
+    let mut translated = token("Hello.", true);
+    translated["translation_status"] = "translation".into();
+    translated["source_language"] = "es".into();
+    let server = MockServer::start(vec![vec![
+        Step::WaitAudio(320),
+        Step::Tokens(serde_json::json!([translated, token("<end>", true)])),
+    ]])
+    .await;
+    let mut harness = Harness::spawn(&server);
+
+    let event = harness
+        .wait_for(|e| matches!(e, SonioxEvent::Transcription(_)))
+        .await;
+    let SonioxEvent::Transcription(update) = event else {
+        unreachable!()
+    };
+    assert!(update.tokens[0].is_translation());
+    assert_eq!(update.tokens[0].translation, TranslationStatus::Translation);
+    assert_eq!(
+        update.tokens[0].source_language,
+        Some(LanguageHint::Spanish)
+    );
+    assert!(!update.tokens[0].is_endpoint());
+    assert!(update.tokens[1].is_endpoint());
+    assert_eq!(update.tokens[1].text, "");
+    assert_eq!(
+        update.tokens[1].translation,
+        TranslationStatus::Untranslated
+    );
+}
+
+#[tokio::test]
+async fn ignores_malformed_messages() {
+    let server = MockServer::start(vec![vec![
+        Step::Raw("definitely not json"),
+        Step::Tokens(serde_json::json!([token("still here", false)])),
+    ]])
+    .await;
+    let mut harness = Harness::spawn(&server);
+
+    let event = harness
+        .wait_for(|e| matches!(e, SonioxEvent::Transcription(_)))
+        .await;
+    let SonioxEvent::Transcription(response) = event else {
+        unreachable!()
+    };
+    assert_eq!(response.tokens[0].text, "still here");
+    assert_eq!(server.connections(), 1);
+}
+
+#[tokio::test]
+async fn fatal_error_stops_worker() {
+    let server = MockServer::start(vec![vec![Step::Error(401, "Invalid API key")]]).await;
+    let mut harness = Harness::spawn(&server);
+
+    let event = harness
+        .wait_for(|e| matches!(e, SonioxEvent::Error(_)))
+        .await;
+    assert!(matches!(
+        event,
+        SonioxEvent::Error(SonioxLiveErrors::API(401, ref msg)) if msg == "Invalid API key"
+    ));
+    assert!(harness.stop().await.is_ok());
+    assert_eq!(server.connections(), 1);
+}
+
+#[tokio::test]
+async fn temporary_errors_reconnect() {
+    for code in [408, 502, 503] {
+        let server = MockServer::start(vec![
+            vec![Step::Error(code, "try again")],
+            vec![Step::Tokens(serde_json::json!([token("back", true)]))],
+        ])
+        .await;
+        let mut harness = Harness::spawn_with(&server.url(), fast_policy());
+
+        harness
+            .wait_for(|e| matches!(e, SonioxEvent::Connected(false)))
+            .await;
+        let event = harness
+            .wait_for(|e| matches!(e, SonioxEvent::Transcription(_)))
+            .await;
+        let SonioxEvent::Transcription(response) = event else {
+            unreachable!()
+        };
+        assert_eq!(response.tokens[0].text, "back");
+        assert_eq!(server.connections(), 2, "code {} must reconnect", code);
+    }
+}
+
+#[tokio::test]
+async fn close_frame_reconnects() {
+    let server = MockServer::start(vec![vec![Step::WaitAudio(2), Step::Close], vec![]]).await;
+    let mut harness = Harness::spawn_with(&server.url(), fast_policy());
+
+    assert!(matches!(
+        harness.next_event().await,
+        SonioxEvent::Connected(true)
+    ));
+    harness
+        .wait_for(|e| matches!(e, SonioxEvent::Connected(false)))
+        .await;
+    server.wait_connections(2).await;
+    assert_eq!(server.connections(), 2);
+}
+
+#[tokio::test]
+async fn failing_sessions_back_off() {
+    let failing = vec![Step::Error(503, "unavailable")];
+    let server = MockServer::start(vec![failing.clone(), failing.clone(), failing]).await;
+    let policy = RetryPolicy {
+        max_attempts: 2,
+        ..fast_policy()
+    };
+    let mut harness = Harness::spawn_with(&server.url(), policy);
+
+    for expected in 1..=2 {
+        let event = harness
+            .wait_for(|e| matches!(e, SonioxEvent::Reconnecting { .. }))
+            .await;
+        assert!(
+            matches!(event, SonioxEvent::Reconnecting { attempt, delay } if attempt == expected && delay == Duration::from_millis(100)),
+            "unexpected event {:?}",
+            event
+        );
+    }
+    harness
+        .wait_for(|e| matches!(e, SonioxEvent::Error(SonioxLiveErrors::ConnectionLost)))
+        .await;
+    assert_eq!(server.connections(), 3);
+    assert!(matches!(
+        harness.stop().await,
+        Err(SonioxLiveErrors::ConnectionLost)
+    ));
+}
+
+#[tokio::test]
+async fn worker_stops_when_audio_closes() {
+    let server = MockServer::start(vec![]).await;
+    let mut harness = Harness::spawn(&server);
+
+    assert!(matches!(
+        harness.next_event().await,
+        SonioxEvent::Connected(true)
+    ));
+    assert!(harness.stop().await.is_ok());
+}
+
+#[tokio::test]
+async fn gives_up_after_max_attempts() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    let policy = RetryPolicy {
+        initial_delay_ms: 10,
+        max_delay_ms: 20,
+        max_attempts: 2,
+        ..Default::default()
+    };
+    let mut harness = Harness::spawn_with(&url, policy);
+
+    for expected in 1..=2 {
+        let event = harness.next_event().await;
+        assert!(
+            matches!(event, SonioxEvent::Reconnecting { attempt, .. } if attempt == expected),
+            "unexpected event {:?}",
+            event
+        );
+    }
+    assert!(matches!(
+        harness.next_event().await,
+        SonioxEvent::Error(SonioxLiveErrors::ConnectionLost)
+    ));
+    assert!(matches!(
+        harness.stop().await,
+        Err(SonioxLiveErrors::ConnectionLost)
+    ));
+}
+
+fn fast_policy() -> RetryPolicy {
+    RetryPolicy {
+        initial_delay_ms: 100,
+        max_delay_ms: 100,
+        jitter: 0.0,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn replays_audio_captured_during_outage() {
+    let server = MockServer::start(vec![vec![Step::Reject], vec![]]).await;
+    let (mut harness, tx_audio, _rx_recycle) = Harness::spawn_manual(
+        &server.url(),
+        fast_policy(),
+        AudioBacklog::new(16000),
+        KEEPALIVE_INTERVAL,
+    );
+
+    for _ in 0..10 {
+        tx_audio.send(vec![1; 160]).await.unwrap();
+    }
+    harness
+        .wait_for(|e| matches!(e, SonioxEvent::Connected(true)))
+        .await;
+    server.wait_audio(0, 3200).await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(server.recorded.lock().unwrap().audio_bytes[0], 3200);
+}
+
+#[tokio::test]
+async fn backlog_keeps_only_most_recent_audio() {
+    let server = MockServer::start(vec![vec![Step::Reject], vec![]]).await;
+    let (mut harness, tx_audio, _rx_recycle) = Harness::spawn_manual(
+        &server.url(),
+        fast_policy(),
+        AudioBacklog::new(480),
+        KEEPALIVE_INTERVAL,
+    );
+
+    for _ in 0..10 {
+        tx_audio.send(vec![1; 160]).await.unwrap();
+    }
+    harness
+        .wait_for(|e| matches!(e, SonioxEvent::Connected(true)))
+        .await;
+    server.wait_audio(0, 960).await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(server.recorded.lock().unwrap().audio_bytes[0], 960);
+}
+
+#[tokio::test]
+async fn timestamps_follow_the_capture_clock() {
+    let mut timed = token("later", true);
+    timed["start_ms"] = 0.into();
+    timed["end_ms"] = 50.into();
+    let server = MockServer::start(vec![
+        vec![Step::WaitAudio(1600), Step::Close],
+        vec![
+            Step::WaitAudio(1600),
+            Step::Tokens(serde_json::json!([timed])),
+        ],
+    ])
+    .await;
+    let clock = Arc::new(AudioClock::new(16000, 1));
+    let (mut harness, tx_audio, _rx_recycle) = Harness::spawn_clocked(
+        &server.url(),
+        fast_policy(),
+        AudioBacklog::new(16000),
+        KEEPALIVE_INTERVAL,
+        Some(clock.clone()),
+    );
+    let send = |count: usize| {
+        let clock = clock.clone();
+        let tx_audio = tx_audio.clone();
+        async move {
+            for _ in 0..count {
+                let captured = clock.capture(160);
+                clock.upload(captured, 160);
+                tx_audio.send(vec![1; 160]).await.unwrap();
+            }
+        }
+    };
+
+    // One second the voice gate dropped before the first speech.
+    clock.capture(16000);
+    send(5).await;
+    harness
+        .wait_for(|e| matches!(e, SonioxEvent::Reconnecting { .. }))
+        .await;
+    send(5).await;
+
+    let event = harness
+        .wait_for(|e| matches!(e, SonioxEvent::Transcription(_)))
+        .await;
+    let SonioxEvent::Transcription(update) = event else {
+        unreachable!()
+    };
+    assert_eq!(update.tokens[0].start_ms, Some(1050.0));
+    assert_eq!(update.tokens[0].end_ms, Some(1100.0));
+}
+
+#[tokio::test]
+async fn finalize_command_is_sent_as_control_message() {
+    let server = MockServer::start(vec![]).await;
+    let mut harness = Harness::spawn(&server);
+
+    harness
+        .wait_for(|e| matches!(e, SonioxEvent::Connected(true)))
+        .await;
+    harness
+        .commands
+        .send(WorkerCommand::Finalize)
+        .await
+        .unwrap();
+    server.wait_control("finalize").await;
+    assert_eq!(server.connections(), 1);
+}
+
+#[tokio::test]
+async fn idle_session_sends_keepalive() {
+    let server = MockServer::start(vec![]).await;
+    let (mut harness, tx_audio, _rx_recycle) = Harness::spawn_manual(
+        &server.url(),
+        RetryPolicy::default(),
+        AudioBacklog::new(16000),
+        Duration::from_millis(100),
+    );
+
+    tx_audio.send(vec![1; 160]).await.unwrap();
+    harness
+        .wait_for(|e| matches!(e, SonioxEvent::Connected(true)))
+        .await;
+    server.wait_control("keepalive").await;
+    let recorded = server.recorded.lock().unwrap();
+    assert!(recorded.controls.iter().all(|c| c["type"] == "keepalive"));
+}
+
+fn manual_worker(
+    retry_policy: RetryPolicy,
+) -> (
+    SonioxWorker,
+    Sender<AudioSample>,
+    Sender<WorkerCommand>,
+    Receiver<SonioxEvent>,
+) {
+    let (tx_audio, rx_audio) = channel::<AudioSample>(256);
+    let (tx_recycle, _rx_recycle) = channel::<AudioSample>(256);
+    let (tx_event, rx_event) = channel::<SonioxEvent>(128);
+    let (commands, rx_command) = channel::<WorkerCommand>(8);
+    let worker = SonioxWorker::new(
+        rx_audio,
+        rx_command,
+        tx_recycle,
+        tx_event,
+        retry_policy,
+        AudioBacklog::new(16000),
+    );
+    (worker, tx_audio, commands, rx_event)
+}
+
+#[tokio::test]
+async fn stopped_worker_serves_new_backend() {
+    let first = MockServer::start(vec![]).await;
+    let second = MockServer::start(vec![]).await;
+    let (mut worker, tx_audio, _commands, mut rx_event) = manual_worker(RetryPolicy::default());
+
+    let backend = soniox_backend(&first.url(), "first-key");
+    let (stop, rx_stop) = oneshot::channel();
+    let task = tokio::spawn(async move {
+        let result = worker.serve(&backend, rx_stop).await;
+        (worker, result)
+    });
+    tx_audio.send(vec![1; 160]).await.unwrap();
+    let event = timeout(EVENT_TIMEOUT, rx_event.recv()).await.unwrap();
+    assert!(matches!(event, Some(SonioxEvent::Connected(true))));
+
+    stop.send(()).unwrap();
+    let (mut worker, result) = timeout(EVENT_TIMEOUT, task).await.unwrap().unwrap();
+    assert!(result.is_ok());
+
+    let backend = soniox_backend(&second.url(), "second-key");
+    let (_stop, rx_stop) = oneshot::channel();
+    let task = tokio::spawn(async move { worker.serve(&backend, rx_stop).await });
+    tx_audio.send(vec![1; 160]).await.unwrap();
+    let event = timeout(EVENT_TIMEOUT, rx_event.recv()).await.unwrap();
+    assert!(matches!(event, Some(SonioxEvent::Connected(true))));
+    second.wait_audio(0, 320).await;
+
+    assert_eq!(first.connections(), 1);
+    assert_eq!(
+        second.recorded.lock().unwrap().configs[0]["api_key"],
+        "second-key"
+    );
+    task.abort();
+}
+
+#[tokio::test]
+async fn stop_interrupts_reconnect_backoff() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    drop(listener);
+    let policy = RetryPolicy {
+        initial_delay_ms: 60_000,
+        max_delay_ms: 60_000,
+        jitter: 0.0,
+        ..Default::default()
+    };
+    let (mut worker, tx_audio, _commands, mut rx_event) = manual_worker(policy);
+
+    let backend = soniox_backend(&url, "test-key");
+    let (stop, rx_stop) = oneshot::channel();
+    let task = tokio::spawn(async move { worker.serve(&backend, rx_stop).await });
+    tx_audio.send(vec![1; 160]).await.unwrap();
+    let event = timeout(EVENT_TIMEOUT, rx_event.recv()).await.unwrap();
+    assert!(matches!(
+        event,
+        Some(SonioxEvent::Reconnecting { attempt: 1, .. })
+    ));
+
+    stop.send(()).unwrap();
+    let result = timeout(EVENT_TIMEOUT, task).await.unwrap().unwrap();
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn stop_delivers_pending_text_before_closing() {
+    let server = MockServer::start(vec![vec![
+        Step::WaitAudio(320),
+        Step::Tokens(serde_json::json!([token("Good", false)])),
+        Step::WaitEnd,
+        Step::Tokens(serde_json::json!([
+            token("Goodbye", true),
+            token("<fin>", true)
+        ])),
+        Step::Close,
+    ]])
+    .await;
+    let (mut worker, tx_audio, _commands, mut rx_event) = manual_worker(RetryPolicy::default());
+
+    let backend = soniox_backend(&server.url(), "test-key");
+    let (stop, rx_stop) = oneshot::channel();
+    let task = tokio::spawn(async move { worker.serve(&backend, rx_stop).await });
+    tx_audio.send(vec![1; 320]).await.unwrap();
+    let event = timeout(EVENT_TIMEOUT, rx_event.recv()).await.unwrap();
+    assert!(matches!(event, Some(SonioxEvent::Connected(true))));
+    let event = timeout(EVENT_TIMEOUT, rx_event.recv()).await.unwrap();
+    assert!(matches!(event, Some(SonioxEvent::Transcription(_))));
+
+    stop.send(()).unwrap();
+    let result = timeout(EVENT_TIMEOUT, task).await.unwrap().unwrap();
+    assert!(result.is_ok());
+    let Ok(SonioxEvent::Transcription(update)) = rx_event.try_recv() else {
+        panic!("finalized text wasn't delivered");
+    };
+    assert_eq!(update.tokens[0].text, "Goodbye");
+    assert!(update.tokens[0].is_final);
+    let recorded = server.recorded.lock().unwrap();
+    assert_eq!(recorded.controls[0]["type"], "finalize");
+    assert_eq!(recorded.ended, vec![0]);
+}
+
+#[tokio::test]
+async fn back_to_back_restarts_serve_the_last_backend() {
+    let first = MockServer::start(vec![]).await;
+    let second = MockServer::start(vec![]).await;
+    let third = MockServer::start(vec![]).await;
+    let (worker, tx_audio, _commands, mut rx_event) = manual_worker(RetryPolicy::default());
+    let (tx_event, _rx_errors) = channel::<SonioxEvent>(8);
+
+    let mut task = WorkerTask::spawn(worker, soniox_backend(&first.url(), "first-key"), tx_event);
+    tx_audio.send(vec![1; 160]).await.unwrap();
+    let event = timeout(EVENT_TIMEOUT, rx_event.recv()).await.unwrap();
+    assert!(matches!(event, Some(SonioxEvent::Connected(true))));
+
+    task.restart(
+        soniox_backend(&second.url(), "second-key"),
+        RetryPolicy::default(),
+    )
+    .unwrap();
+    task.restart(
+        soniox_backend(&third.url(), "third-key"),
+        RetryPolicy::default(),
+    )
+    .unwrap();
+    let feeder = tokio::spawn(async move {
+        while tx_audio.send(vec![1; 160]).await.is_ok() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    });
+    third.wait_connections(1).await;
+    feeder.abort();
+    assert_eq!(
+        third.recorded.lock().unwrap().configs[0]["api_key"],
+        "third-key"
+    );
+    assert_eq!(first.connections(), 1);
+}